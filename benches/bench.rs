@@ -85,5 +85,51 @@ fn bench_indexing(c: &mut Criterion) {
 #[cfg(not(feature = "embed-ipadic"))]
 fn bench_indexing(_c: &mut Criterion) {}
 
-criterion_group!(benches, bench_indexing,);
+#[cfg(feature = "embed-ipadic")]
+fn bench_tokenize_batch(c: &mut Criterion) {
+    use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+    use lindera::dictionary::load_dictionary;
+    use lindera::mode::Mode;
+    use lindera::segmenter::Segmenter;
+    use lindera_tantivy::tokenizer::LinderaTokenizer;
+
+    // Test text set.
+    let texts: Vec<String> = (0..1000)
+        .map(|i| format!("成田国際空港（なりたこくさいくうこう、英: Narita International Airport）は、千葉県成田市南東部から芝山町北部にかけて建設された日本最大の国際拠点空港である[{i}]。首都圏東部（東京の東60km）に位置している。空港コードはNRT。"))
+        .collect();
+    let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+
+    let mode = Mode::Normal;
+    let dictionary = load_dictionary("embedded://ipadic").unwrap();
+    let user_dictionary = None;
+    let segmenter = Segmenter::new(mode, dictionary, user_dictionary);
+    let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+
+    let mut group = c.benchmark_group("tokenize");
+    group.sample_size(10);
+    group.bench_function("bench-token-stream", |b| {
+        b.iter(|| {
+            texts
+                .iter()
+                .map(|text| {
+                    let mut tokens: Vec<Token> = Vec::new();
+                    tokenizer
+                        .token_stream(text)
+                        .process(&mut |token| tokens.push(token.clone()));
+                    tokens
+                })
+                .collect::<Vec<Vec<Token>>>()
+        });
+    });
+    group.bench_function("bench-tokenize-batch", |b| {
+        b.iter(|| tokenizer.tokenize_batch(&texts).unwrap());
+    });
+    group.finish();
+}
+
+#[cfg(not(feature = "embed-ipadic"))]
+fn bench_tokenize_batch(_c: &mut Criterion) {}
+
+criterion_group!(benches, bench_indexing, bench_tokenize_batch,);
 criterion_main!(benches);
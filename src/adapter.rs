@@ -0,0 +1,309 @@
+//! Adapters exposing Lindera token filters as Tantivy token filters.
+//!
+//! This module provides the [`LinderaTokenFilter`] struct, which implements Tantivy's
+//! [`TokenFilter`] trait with a Lindera token filter, so the same filter implementation
+//! can be chained onto any Tantivy tokenizer with `TextAnalyzer::builder`.
+
+use std::borrow::Cow;
+use std::sync::{Arc, LazyLock};
+
+use daachorse::DoubleArrayAhoCorasick;
+use tantivy_tokenizer_api::{Token, TokenFilter, TokenStream, Tokenizer};
+
+use lindera::dictionary::{Dictionary, Metadata, WordId};
+use lindera::token::Token as LToken;
+use lindera::token_filter::BoxTokenFilter;
+use lindera_dictionary::dictionary::character_definition::{CharacterDefinition, LookupTable};
+use lindera_dictionary::dictionary::connection_cost_matrix::ConnectionCostMatrix;
+use lindera_dictionary::dictionary::prefix_dictionary::PrefixDictionary;
+use lindera_dictionary::dictionary::unknown_dictionary::UnknownDictionary;
+
+use crate::stream::LinderaTokenStream;
+
+/// A Tantivy token filter that applies a Lindera token filter.
+///
+/// The tokens of the wrapped Tantivy tokenizer are converted to Lindera tokens, passed
+/// through the Lindera token filter, and converted back. Since these tokens do not come
+/// from morphological analysis, they are treated as unknown words, whose details are `UNK`
+/// padded with `*` to the fields of the dictionary schema. Filters working on the surface
+/// form (lowercase, stop words, katakana stem, etc.) behave as they do in
+/// [`LinderaTokenizer`](crate::tokenizer::LinderaTokenizer), while filters working on
+/// part-of-speech tags see no tags.
+///
+/// If the Lindera token filter fails, the token stream panics, as the one of
+/// [`LinderaTokenizer`](crate::tokenizer::LinderaTokenizer) does.
+///
+/// Lindera tokens refer to a dictionary. [`LinderaTokenFilter::new`] gives them an empty
+/// one, which is enough for filters working on the surface form. Filters reading the
+/// details of the tokens need the schema of a real dictionary, given with
+/// [`LinderaTokenFilter::with_dictionary`].
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashSet;
+///
+/// use lindera::token_filter::lowercase::LowercaseTokenFilter;
+/// use lindera::token_filter::stop_words::StopWordsTokenFilter;
+/// use lindera_tantivy::adapter::LinderaTokenFilter;
+/// use tantivy::tokenizer::{SimpleTokenizer, TextAnalyzer};
+///
+/// let stop_words = HashSet::from(["the".to_string()]);
+///
+/// let analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+///     .filter(LinderaTokenFilter::new(LowercaseTokenFilter::new().into()))
+///     .filter(LinderaTokenFilter::new(
+///         StopWordsTokenFilter::new(stop_words).into(),
+///     ))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct LinderaTokenFilter {
+    token_filter: Arc<BoxTokenFilter>,
+    dictionary: Arc<Dictionary>,
+}
+
+/// The dictionary of the tokens of filters given no dictionary. It has no words and the
+/// default schema, and is never used to segment text.
+static EMPTY_DICTIONARY: LazyLock<Arc<Dictionary>> = LazyLock::new(|| {
+    let da = DoubleArrayAhoCorasick::<u32>::new(["\0"])
+        .expect("a double array of a single pattern can always be built");
+    Arc::new(Dictionary {
+        prefix_dictionary: PrefixDictionary {
+            da,
+            vals_data: Vec::new().into(),
+            words_idx_data: Vec::new().into(),
+            words_data: Vec::new().into(),
+            is_system: true,
+        },
+        connection_cost_matrix: ConnectionCostMatrix {
+            costs_data: Vec::new(),
+            backward_size: 0,
+            forward_size: 0,
+        },
+        character_definition: CharacterDefinition {
+            category_definitions: Vec::new(),
+            category_names: Vec::new(),
+            mapping: LookupTable::from_fn(Vec::new(), &|_, _| {}),
+        },
+        unknown_dictionary: UnknownDictionary {
+            category_references: Vec::new(),
+            costs: Vec::new(),
+            words_idx_data: Vec::new(),
+            words_data: Vec::new(),
+        },
+        metadata: Metadata::default(),
+    })
+});
+
+impl LinderaTokenFilter {
+    /// Creates a new `LinderaTokenFilter` for a filter working on the surface form.
+    ///
+    /// The tokens refer to an empty dictionary, so no dictionary has to be loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_filter` - The Lindera token filter to apply
+    pub fn new(token_filter: BoxTokenFilter) -> LinderaTokenFilter {
+        LinderaTokenFilter::with_dictionary(token_filter, EMPTY_DICTIONARY.clone())
+    }
+
+    /// Creates a new `LinderaTokenFilter` whose tokens refer to the given dictionary.
+    ///
+    /// The details of the tokens are padded to the fields of the dictionary schema, for
+    /// filters that read them. The dictionary is shared between the clones of the filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_filter` - The Lindera token filter to apply
+    /// * `dictionary` - The dictionary the Lindera tokens refer to
+    pub fn with_dictionary(
+        token_filter: BoxTokenFilter,
+        dictionary: Arc<Dictionary>,
+    ) -> LinderaTokenFilter {
+        LinderaTokenFilter {
+            token_filter: Arc::new(token_filter),
+            dictionary,
+        }
+    }
+}
+
+impl TokenFilter for LinderaTokenFilter {
+    type Tokenizer<T: Tokenizer> = LinderaTokenFilterWrapper<T>;
+
+    fn transform<T: Tokenizer>(self, tokenizer: T) -> LinderaTokenFilterWrapper<T> {
+        LinderaTokenFilterWrapper {
+            tokenizer,
+            token_filter: self.token_filter,
+            dictionary: self.dictionary,
+            token: Default::default(),
+        }
+    }
+}
+
+/// A Tantivy tokenizer that applies a Lindera token filter to the tokens of another
+/// tokenizer.
+///
+/// This struct is created by [`LinderaTokenFilter`] when it is added to a
+/// `TextAnalyzer`.
+#[derive(Clone)]
+pub struct LinderaTokenFilterWrapper<T> {
+    tokenizer: T,
+    token_filter: Arc<BoxTokenFilter>,
+    dictionary: Arc<Dictionary>,
+    token: Token,
+}
+
+impl<T: Tokenizer> Tokenizer for LinderaTokenFilterWrapper<T> {
+    type TokenStream<'a> = LinderaTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> LinderaTokenStream<'a> {
+        self.token.reset();
+
+        let dictionary: &Dictionary = &self.dictionary;
+        let details_len = dictionary
+            .metadata
+            .dictionary_schema
+            .get_custom_fields()
+            .len()
+            .max(1);
+        let mut details = vec![Cow::Borrowed("*"); details_len];
+        details[0] = Cow::Borrowed("UNK");
+
+        let mut tokens = Vec::new();
+        let mut token_stream = self.tokenizer.token_stream(text);
+        while let Some(token) = token_stream.next() {
+            tokens.push(LToken {
+                surface: Cow::Owned(token.text.clone()),
+                byte_start: token.offset_from,
+                byte_end: token.offset_to,
+                position: token.position,
+                position_length: token.position_length,
+                word_id: WordId::default(),
+                dictionary,
+                user_dictionary: None,
+                details: Some(details.clone()),
+            });
+        }
+        if let Err(e) = self.token_filter.apply(&mut tokens) {
+            panic!(
+                "failed to apply the {} token filter: {e:?}",
+                self.token_filter.name()
+            );
+        }
+
+        LinderaTokenStream {
+            tokens,
+            token: &mut self.token,
+            current_index: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use lindera::LinderaResult;
+    use lindera::error::LinderaErrorKind;
+    use lindera::token::Token as LToken;
+    use lindera::token_filter::TokenFilter;
+    use lindera::token_filter::lowercase::LowercaseTokenFilter;
+    use lindera::token_filter::stop_words::StopWordsTokenFilter;
+    use tantivy::tokenizer::{SimpleTokenizer, TextAnalyzer};
+    use tantivy_tokenizer_api::Token;
+
+    use super::LinderaTokenFilter;
+
+    fn texts(analyzer: &mut TextAnalyzer, text: &str) -> Vec<String> {
+        let mut texts: Vec<String> = vec![];
+        analyzer
+            .token_stream(text)
+            .process(&mut |token: &Token| texts.push(token.text.clone()));
+        texts
+    }
+
+    #[test]
+    fn test_lindera_token_filter_on_simple_tokenizer() {
+        let stop_words = HashSet::from(["the".to_string()]);
+
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LinderaTokenFilter::new(LowercaseTokenFilter::new().into()))
+            .filter(LinderaTokenFilter::new(
+                StopWordsTokenFilter::new(stop_words).into(),
+            ))
+            .build();
+
+        let mut tokens: Vec<Token> = vec![];
+        analyzer
+            .token_stream("The Quick Brown Fox")
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, vec!["quick", "brown", "fox"]);
+        assert_eq!(tokens[0].position, 1);
+        assert_eq!((tokens[0].offset_from, tokens[0].offset_to), (4, 9));
+    }
+
+    /// Replaces the surface of each token with its first detail and number of details, and
+    /// fails on "fail".
+    #[derive(Clone)]
+    struct DetailsTokenFilter;
+
+    impl TokenFilter for DetailsTokenFilter {
+        fn name(&self) -> &'static str {
+            "details"
+        }
+
+        fn apply(&self, tokens: &mut Vec<LToken<'_>>) -> LinderaResult<()> {
+            for token in tokens.iter_mut() {
+                if token.surface == "fail" {
+                    return Err(LinderaErrorKind::Args.with_error(std::io::Error::other("fail")));
+                }
+                let details = token.details();
+                token.surface = format!("{}:{}", details[0], details.len()).into();
+            }
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_lindera_token_filter_details() {
+        use std::sync::Arc;
+
+        use lindera::dictionary::load_dictionary;
+
+        let dictionary = Arc::new(load_dictionary("embedded://ipadic").unwrap());
+        let fields = dictionary
+            .metadata
+            .dictionary_schema
+            .get_custom_fields()
+            .len();
+
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LinderaTokenFilter::with_dictionary(
+                DetailsTokenFilter.into(),
+                dictionary,
+            ))
+            .build();
+
+        let expected = format!("UNK:{fields}");
+        assert_eq!(
+            texts(&mut analyzer, "quick fox"),
+            vec![expected.clone(), expected]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to apply the details token filter")]
+    fn test_lindera_token_filter_error() {
+        let mut analyzer = TextAnalyzer::builder(SimpleTokenizer::default())
+            .filter(LinderaTokenFilter::new(DetailsTokenFilter.into()))
+            .build();
+
+        assert_eq!(texts(&mut analyzer, "quick fox").len(), 2);
+        texts(&mut analyzer, "quick fail fox");
+    }
+}
@@ -0,0 +1,258 @@
+//! Document-level analysis.
+//!
+//! This module provides the [`DocumentAnalyzer`], which analyzes the text fields of a
+//! document ahead of indexing and replaces them with pre-tokenized text, so that the
+//! analysis runs outside of the `IndexWriter` threads, can be parallelized across fields
+//! and documents, and can be measured on its own.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use tantivy::Result;
+use tantivy::TantivyDocument;
+use tantivy::schema::{Field, Value};
+use tantivy::tokenizer::PreTokenizedString;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::tokenizer::LinderaTokenizer;
+
+/// An analyzer of the text fields of documents.
+///
+/// Each field is mapped to a [`LinderaTokenizer`], and fields mapped together share the same
+/// tokenizer. [`analyze`](Self::analyze) returns a copy of
+/// the document where the text values of the mapped fields are replaced with
+/// [`PreTokenizedString`] values, which Tantivy indexes as is instead of running the
+/// tokenizer of the field; the values of the other fields are copied unchanged.
+///
+/// With the `parallel` feature, the values of a document and the documents of a batch are
+/// analyzed in parallel on the global rayon thread pool, or on the pool the analyzer is
+/// called from with `ThreadPool::install`. The tokenizers are shared between the threads
+/// without being cloned.
+///
+/// The mapped fields still need to be indexed text fields, and Tantivy requires the
+/// tokenizers named in their indexing options to be registered in the index even though
+/// they are not run. A stored mapped field stores the pre-tokenized string.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::analyzer::DocumentAnalyzer;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+/// use tantivy::schema::{Schema, TEXT};
+/// use tantivy::{Index, doc};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut schema_builder = Schema::builder();
+/// let title = schema_builder.add_text_field("title", TEXT);
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let summary = schema_builder.add_text_field("summary", TEXT);
+/// let index = Index::create_in_ram(schema_builder.build());
+///
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// let mut analyzer = DocumentAnalyzer::new();
+/// analyzer.add_fields(&[title, body, summary], tokenizer);
+///
+/// let documents = vec![doc!(
+///     title => "成田国際空港",
+///     body => "成田国際空港は千葉県成田市にある国際空港です。",
+///     summary => "千葉県の空港",
+/// )];
+/// let documents = analyzer.analyze_documents(&documents)?;
+///
+/// let mut index_writer = index.writer(50_000_000)?;
+/// for document in documents {
+///     index_writer.add_document(document)?;
+/// }
+/// index_writer.commit()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct DocumentAnalyzer {
+    tokenizers: HashMap<Field, Arc<LinderaTokenizer>>,
+}
+
+impl DocumentAnalyzer {
+    /// Creates a new `DocumentAnalyzer` without any mapped field.
+    pub fn new() -> DocumentAnalyzer {
+        DocumentAnalyzer::default()
+    }
+
+    /// Maps fields to a tokenizer.
+    ///
+    /// A field mapped again is remapped to the new tokenizer, and a tokenizer no field is
+    /// mapped to anymore is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields` - The fields to analyze with the tokenizer
+    /// * `tokenizer` - The tokenizer, which is shared by the fields
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn add_fields(&mut self, fields: &[Field], tokenizer: LinderaTokenizer) -> &mut Self {
+        let tokenizer = Arc::new(tokenizer);
+        for field in fields {
+            self.tokenizers.insert(*field, Arc::clone(&tokenizer));
+        }
+
+        self
+    }
+
+    /// Analyzes the text fields of a document.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document to analyze
+    ///
+    /// # Returns
+    ///
+    /// Returns a copy of the document with the text values of the mapped fields replaced
+    /// with pre-tokenized text, or an error if the analysis of a value fails.
+    pub fn analyze(&self, document: &TantivyDocument) -> Result<TantivyDocument> {
+        // The text values to analyze, with the tokenizer of their field.
+        let texts: Vec<(&LinderaTokenizer, &str)> = document
+            .field_values()
+            .filter_map(|(field, value)| {
+                let tokenizer = self.tokenizers.get(&field)?;
+                Some((tokenizer.as_ref(), value.as_str()?))
+            })
+            .collect();
+
+        #[cfg(feature = "parallel")]
+        let texts = texts.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let texts = texts.into_iter();
+
+        let mut pre_tokenized_texts = texts
+            .map(|(tokenizer, text)| tokenizer.pre_tokenize(text))
+            .collect::<Result<Vec<PreTokenizedString>>>()?
+            .into_iter();
+
+        let mut analyzed = TantivyDocument::new();
+        for (field, value) in document.field_values() {
+            if self.tokenizers.contains_key(&field)
+                && value.as_str().is_some()
+                && let Some(pre_tokenized_text) = pre_tokenized_texts.next()
+            {
+                analyzed.add_pre_tokenized_text(field, pre_tokenized_text);
+            } else {
+                analyzed.add_field_value(field, value);
+            }
+        }
+
+        Ok(analyzed)
+    }
+
+    /// Analyzes the text fields of a batch of documents.
+    ///
+    /// # Arguments
+    ///
+    /// * `documents` - The documents to analyze
+    ///
+    /// # Returns
+    ///
+    /// Returns copies of the documents in the same order, with the text values of the
+    /// mapped fields replaced with pre-tokenized text, or an error if the analysis of a
+    /// value fails.
+    pub fn analyze_documents(&self, documents: &[TantivyDocument]) -> Result<Vec<TantivyDocument>> {
+        #[cfg(feature = "parallel")]
+        let documents = documents.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let documents = documents.iter();
+
+        documents.map(|document| self.analyze(document)).collect()
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "embed-ipadic")]
+mod tests {
+    use tantivy::collector::Count;
+    use tantivy::query::QueryParser;
+    use tantivy::schema::{Schema, Value};
+    use tantivy::{TantivyDocument, doc};
+
+    use crate::test_helpers::{create_index, ipadic_tokenizer, text_options};
+
+    use super::DocumentAnalyzer;
+
+    #[test]
+    fn test_analyze_documents() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", text_options());
+        let body = schema_builder.add_text_field("body", text_options());
+        let id = schema_builder.add_u64_field("id", tantivy::schema::STORED);
+        let schema = schema_builder.build();
+
+        let mut analyzer = DocumentAnalyzer::new();
+        analyzer.add_fields(&[title, body], ipadic_tokenizer());
+
+        let documents = analyzer
+            .analyze_documents(&[
+                doc!(id => 1u64, title => "東京", body => "東京国際空港"),
+                doc!(id => 2u64, title => "羽田", body => "羽田空港"),
+            ])
+            .unwrap();
+        let analyzed: &TantivyDocument = &documents[0];
+        assert_eq!(
+            analyzed.get_first(id).and_then(|value| value.as_u64()),
+            Some(1)
+        );
+        assert!(analyzed.get_first(title).unwrap().as_str().is_none());
+
+        let index = create_index(schema, documents);
+
+        let query_parser = QueryParser::for_index(&index, vec![title, body]);
+        let searcher = index.reader().unwrap().searcher();
+        let count = |text: &str| {
+            let query = query_parser.parse_query(text).unwrap();
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("空港"), 2);
+        assert_eq!(count("東京"), 1);
+    }
+
+    #[test]
+    fn test_add_fields_again() {
+        use crate::token_filter::japanese_romaji::JapaneseRomajiTokenFilter;
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", text_options());
+        let body = schema_builder.add_text_field("body", text_options());
+
+        let mut tokenizer = ipadic_tokenizer();
+        tokenizer.append_token_filter(JapaneseRomajiTokenFilter::new(false).into());
+
+        let mut analyzer = DocumentAnalyzer::new();
+        analyzer
+            .add_fields(&[title, body], ipadic_tokenizer())
+            .add_fields(&[title], tokenizer);
+
+        let analyzed = analyzer
+            .analyze(&doc!(title => "東京", body => "東京"))
+            .unwrap();
+        let texts = |field| -> Vec<String> {
+            let pre_tokenized_text = analyzed
+                .get_first(field)
+                .and_then(|value| value.as_pre_tokenized_text())
+                .unwrap();
+            pre_tokenized_text
+                .tokens
+                .iter()
+                .map(|token| token.text.clone())
+                .collect()
+        };
+        assert_eq!(texts(title), vec!["東京", "tokyo"]);
+        assert_eq!(texts(body), vec!["東京"]);
+    }
+}
@@ -0,0 +1,197 @@
+//! Search-as-you-type support.
+//!
+//! This module provides the pieces of an autocomplete field: the field options, the
+//! tokenizer of the field built by [`index_tokenizer`], which emits the edge n-grams of
+//! each token, and [`autocomplete_query`], which looks up the raw input of the user among
+//! the indexed n-grams.
+//!
+//! # Examples
+//!
+//! ```rust,ignore
+//! use lindera::dictionary::load_dictionary;
+//! use lindera::mode::Mode;
+//! use lindera::segmenter::Segmenter;
+//! use lindera_tantivy::autocomplete::{autocomplete_query, index_tokenizer, text_options};
+//! use lindera_tantivy::tokenizer::LinderaTokenizer;
+//! use tantivy::collector::TopDocs;
+//! use tantivy::schema::Schema;
+//! use tantivy::Index;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut schema_builder = Schema::builder();
+//! let suggest = schema_builder.add_text_field("suggest", text_options("lang_ja_suggest"));
+//! let index = Index::create_in_ram(schema_builder.build());
+//!
+//! let dictionary = load_dictionary("embedded://ipadic")?;
+//! let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+//! let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+//! index
+//!     .tokenizers()
+//!     .register("lang_ja_suggest", index_tokenizer(&tokenizer, 1, 10, true)?);
+//!
+//! // ... index documents ...
+//!
+//! let searcher = index.reader()?.searcher();
+//! let query = autocomplete_query(suggest, "とうき", 10);
+//! let suggestions = searcher.search(&query, &TopDocs::with_limit(10))?;
+//! # Ok(())
+//! # }
+//! ```
+
+use tantivy::Result;
+use tantivy::Term;
+use tantivy::query::{BooleanQuery, Occur, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, TextFieldIndexing, TextOptions};
+
+use crate::token_filter::edge_ngram::EdgeNgramTokenFilter;
+use crate::token_filter::korean_jamo::decompose_jamo;
+use crate::tokenizer::LinderaTokenizer;
+
+/// Returns the options of an autocomplete field.
+///
+/// The field is indexed with the given tokenizer, which is expected to emit edge n-grams,
+/// and stored so that the suggestions can be displayed. Positions are not indexed since
+/// n-grams are only looked up as single terms.
+///
+/// # Arguments
+///
+/// * `tokenizer_name` - The name the edge n-gram tokenizer is registered under
+///
+/// # Returns
+///
+/// The `TextOptions` of the autocomplete field.
+pub fn text_options(tokenizer_name: &str) -> TextOptions {
+    TextOptions::default()
+        .set_indexing_options(
+            TextFieldIndexing::default()
+                .set_tokenizer(tokenizer_name)
+                .set_index_option(IndexRecordOption::WithFreqs),
+        )
+        .set_stored()
+}
+
+/// Builds the tokenizer of an autocomplete field.
+///
+/// The tokenizer is a copy of the given one with an [`EdgeNgramTokenFilter`] appended, so
+/// that the prefixes of each token, and of its reading if enabled, are indexed.
+///
+/// # Arguments
+///
+/// * `tokenizer` - The tokenizer segmenting the field
+/// * `min_gram` - The minimum length of the n-grams, in characters
+/// * `max_gram` - The maximum length of the n-grams, in characters
+/// * `include_reading` - Whether to index the n-grams of the reading of each token
+///
+/// # Returns
+///
+/// Returns the tokenizer to register for the field, or an error if the n-gram lengths are
+/// invalid.
+pub fn index_tokenizer(
+    tokenizer: &LinderaTokenizer,
+    min_gram: usize,
+    max_gram: usize,
+    include_reading: bool,
+) -> Result<LinderaTokenizer> {
+    let mut tokenizer = tokenizer.clone();
+    tokenizer.append_token_filter(
+        EdgeNgramTokenFilter::new(min_gram, max_gram, include_reading)?.into(),
+    );
+
+    Ok(tokenizer)
+}
+
+/// Builds a query matching the raw input of a user against an autocomplete field.
+///
+/// The input is not analyzed: each whitespace-separated word is looked up as is among the
+/// indexed n-grams, truncated to `max_gram` characters, and all words must match. Since
+/// the n-grams are made per token, a word spanning several tokens, e.g. "東京空" for
+/// "東京空港", only matches if the user separates the tokens, as in "東京 空".
+///
+/// # Arguments
+///
+/// * `field` - The autocomplete field
+/// * `input` - The text typed by the user so far
+/// * `max_gram` - The maximum length of the indexed n-grams, in characters
+///
+/// # Returns
+///
+/// The query, which matches nothing if the input is blank.
+pub fn autocomplete_query(field: Field, input: &str, max_gram: usize) -> Box<dyn Query> {
+    let subqueries = input
+        .split_whitespace()
+        .map(|word| {
+            let word = match word.char_indices().nth(max_gram) {
+                Some((end, _)) => &word[..end],
+                None => word,
+            };
+            let term = Term::from_field_text(field, word);
+            let query: Box<dyn Query> = Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+            (Occur::Must, query)
+        })
+        .collect();
+
+    Box::new(BooleanQuery::new(subqueries))
+}
+
+/// Builds a query matching the Korean input of a user against an autocomplete field whose
+/// tokenizer stacks the jamo forms of the tokens.
+///
+/// The field tokenizer is expected to have a
+/// [`KoreanJamoTokenFilter`](crate::token_filter::korean_jamo::KoreanJamoTokenFilter)
+/// before the edge n-gram filter, e.g. built by [`index_tokenizer`] from a tokenizer with
+/// the jamo filter appended. The input is decomposed with [`decompose_jamo`] and looked up
+/// as in [`autocomplete_query`], so both an unfinished syllable, as in "하넫", and initial
+/// consonants, as in "ㅎㄴㄷ", match "하네다".
+///
+/// # Arguments
+///
+/// * `field` - The autocomplete field
+/// * `input` - The text typed by the user so far
+/// * `max_gram` - The maximum length of the indexed n-grams, in characters
+///
+/// # Returns
+///
+/// The query, which matches nothing if the input is blank.
+pub fn jamo_query(field: Field, input: &str, max_gram: usize) -> Box<dyn Query> {
+    autocomplete_query(field, &decompose_jamo(input), max_gram)
+}
+
+#[cfg(test)]
+#[cfg(feature = "embed-ipadic")]
+mod tests {
+    use tantivy::collector::Count;
+    use tantivy::doc;
+    use tantivy::schema::Schema;
+
+    use crate::test_helpers::{TOKENIZER_NAME, create_index_with, ipadic_tokenizer};
+
+    use super::{autocomplete_query, index_tokenizer, text_options};
+
+    #[test]
+    fn test_autocomplete_query() {
+        let mut schema_builder = Schema::builder();
+        let suggest = schema_builder.add_text_field("suggest", text_options(TOKENIZER_NAME));
+        let index = create_index_with(
+            schema_builder.build(),
+            index_tokenizer(&ipadic_tokenizer(), 1, 10, true).unwrap(),
+            ["東京空港", "関西空港"].map(|text| doc!(suggest => text)),
+        );
+
+        let searcher = index.reader().unwrap().searcher();
+        let count = |input: &str| {
+            searcher
+                .search(&autocomplete_query(suggest, input, 10), &Count)
+                .unwrap()
+        };
+        assert_eq!(count("とうき"), 1);
+        assert_eq!(count("トウキョ"), 1);
+        assert_eq!(count("東"), 1);
+        assert_eq!(count("くう"), 2);
+        assert_eq!(count("東京 くう"), 1);
+        assert_eq!(count("関西 くう"), 1);
+        // The tokens of the input must be separated.
+        assert_eq!(count("東京空"), 0);
+        assert_eq!(count("大阪"), 0);
+        assert_eq!(count(""), 0);
+    }
+}
@@ -0,0 +1,442 @@
+//! Japanese date extraction.
+//!
+//! This module provides the [`DateExtractor`], which extracts the dates mentioned in a
+//! text, such as "令和5年3月1日" or "2023年3月", and adds them to a date field, so that
+//! documents can be filtered by range on the dates in their text.
+
+use std::collections::HashSet;
+
+use tantivy::DateTime;
+use tantivy::Result;
+use tantivy::TantivyDocument;
+use tantivy::schema::Field;
+
+use crate::quantity::parse_number;
+use crate::tokenizer::LinderaTokenizer;
+
+/// A day of the Gregorian calendar, as a year, a month and a day of the month.
+type Day = (i64, i64, i64);
+
+/// The span of an era, as its first day and, except for the ongoing era, its last day.
+type Span = (Day, Option<Day>);
+
+/// The Japanese eras with their spans. Meiji is counted from the first day of 1868, as it
+/// was applied to the whole year.
+const ERAS: [(&str, Span); 5] = [
+    ("明治", ((1868, 1, 1), Some((1912, 7, 29)))),
+    ("大正", ((1912, 7, 30), Some((1926, 12, 24)))),
+    ("昭和", ((1926, 12, 25), Some((1989, 1, 7)))),
+    ("平成", ((1989, 1, 8), Some((2019, 4, 30)))),
+    ("令和", ((2019, 5, 1), None)),
+];
+
+/// The smallest year written in Gregorian years that is recognized, so that durations
+/// like "3年" are not taken for dates.
+const MIN_GREGORIAN_YEAR: i64 = 1000;
+
+/// The precision of a date extracted from a text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DatePrecision {
+    /// A year, e.g. "2023年", extracted as its first day.
+    Year,
+    /// A month, e.g. "2023年3月", extracted as its first day.
+    Month,
+    /// A day, e.g. "2023年3月1日".
+    Day,
+}
+
+/// A date extracted from a text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtractedDate {
+    /// The date, at midnight UTC.
+    pub date: DateTime,
+    /// The precision of the date expression.
+    pub precision: DatePrecision,
+    /// The byte offset of the start of the date expression in the text.
+    pub byte_start: usize,
+    /// The byte offset of the end of the date expression in the text.
+    pub byte_end: usize,
+}
+
+/// Returns whether a character is a digit in Arabic or kanji numerals, or one of the kanji
+/// units "十", "百" and "千".
+fn is_numeral(c: char) -> bool {
+    c.is_ascii_digit() || ('０'..='９').contains(&c) || "〇一二三四五六七八九十百千".contains(c)
+}
+
+/// Matches a number followed by a suffix at the start of a text, and returns its value
+/// and the byte length of the match.
+fn match_number(text: &str, suffix: char) -> Option<(i64, usize)> {
+    let numeral_len = text
+        .char_indices()
+        .find(|(_, c)| !is_numeral(*c))
+        .map_or(text.len(), |(i, _)| i);
+    if numeral_len == 0 || !text[numeral_len..].starts_with(suffix) {
+        return None;
+    }
+    let value = parse_number(&text[..numeral_len])?;
+
+    Some((value as i64, numeral_len + suffix.len_utf8()))
+}
+
+/// Matches a year in Gregorian or Japanese era years at the start of a text, and returns
+/// the Gregorian year, the byte length of the match, and the span of the era, if any.
+fn match_year(text: &str) -> Option<(i64, usize, Option<Span>)> {
+    for (era, span) in ERAS {
+        if let Some(rest) = text.strip_prefix(era) {
+            let (year, len) = if rest.starts_with("元年") {
+                (1, "元年".len())
+            } else {
+                match_number(rest, '年').filter(|(year, _)| *year >= 1)?
+            };
+            let ((first_year, _, _), _) = span;
+            return Some((first_year + year - 1, era.len() + len, Some(span)));
+        }
+    }
+
+    match_number(text, '年')
+        .filter(|(year, _)| *year >= MIN_GREGORIAN_YEAR)
+        .map(|(year, len)| (year, len, None))
+}
+
+/// Returns whether a year is a leap year of the Gregorian calendar.
+fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Returns the number of days in a month.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the date at midnight UTC of a day of the Gregorian calendar.
+fn to_date_time(year: i64, month: i64, day: i64) -> DateTime {
+    // The number of days since 1970-01-01, computed from the year starting in March.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    DateTime::from_timestamp_secs(days * 86_400)
+}
+
+/// Matches the longest date expression at the start of a text that ends where `is_end`
+/// allows, and returns the date, its precision, and the byte length of the match.
+fn match_date(
+    text: &str,
+    is_end: impl Fn(usize) -> bool,
+) -> Option<(DateTime, DatePrecision, usize)> {
+    let (year, mut len, span) = match_year(text)?;
+    // Whether the days from `first` to `last` overlap the span of the era of the year.
+    let in_era = |first: Day, last: Day| {
+        span.is_none_or(|(first_day, last_day)| {
+            last >= first_day && last_day.is_none_or(|last_day| first <= last_day)
+        })
+    };
+    if !in_era((year, 1, 1), (year, 12, 31)) {
+        return None;
+    }
+    let mut matched = is_end(len).then_some((1, 1, DatePrecision::Year, len));
+
+    if let Some((month, month_len)) = match_number(&text[len..], '月').filter(|(month, _)| {
+        (1..=12).contains(month)
+            && in_era(
+                (year, *month, 1),
+                (year, *month, days_in_month(year, *month)),
+            )
+    }) {
+        len += month_len;
+        if is_end(len) {
+            matched = Some((month, 1, DatePrecision::Month, len));
+        }
+
+        if let Some((day, day_len)) = match_number(&text[len..], '日').filter(|(day, _)| {
+            (1..=days_in_month(year, month)).contains(day)
+                && in_era((year, month, *day), (year, month, *day))
+        }) {
+            len += day_len;
+            if is_end(len) {
+                matched = Some((month, day, DatePrecision::Day, len));
+            }
+        }
+    }
+
+    let (month, day, precision, len) = matched?;
+    Some((to_date_time(year, month, day), precision, len))
+}
+
+/// Parses a Japanese date expression.
+///
+/// The year is written in Gregorian years from 1000, or in the years of the eras from
+/// Meiji to Reiwa, including "元年" for the first year, and can be followed by a month and
+/// a day. A date in era years must fall within the span of the era, so "平成31年5月" is not
+/// a date as Reiwa started on 1 May 2019; the years of Reiwa, the ongoing era, are not
+/// bounded. Numbers can be Arabic, full-width or kanji numerals, including "二千二十三".
+///
+/// # Arguments
+///
+/// * `text` - The date expression
+///
+/// # Returns
+///
+/// Returns the date at midnight UTC, which is the first day of the year or month for a
+/// year or month expression, and its precision, or `None` if the text is not a date.
+///
+/// # Examples
+///
+/// ```
+/// use lindera_tantivy::date::{DatePrecision, parse_date};
+/// use tantivy::DateTime;
+///
+/// let (date, precision) = parse_date("令和5年3月1日").unwrap();
+/// assert_eq!(date, DateTime::from_timestamp_secs(1_677_628_800));
+/// assert_eq!(precision, DatePrecision::Day);
+///
+/// assert_eq!(parse_date("2023年3月").unwrap().1, DatePrecision::Month);
+/// assert_eq!(parse_date("3年"), None);
+/// ```
+pub fn parse_date(text: &str) -> Option<(DateTime, DatePrecision)> {
+    match_date(text, |len| len == text.len()).map(|(date, precision, _)| (date, precision))
+}
+
+/// An extractor of the Japanese dates mentioned in a text.
+///
+/// The text is analyzed with the given [`LinderaTokenizer`], and the date expressions
+/// recognized by [`parse_date`] are searched from the start of each token. An expression
+/// must start and end on token boundaries, so that the numbers inside words are not
+/// taken for dates; for instance "2023年度" is not extracted as the year 2023 since
+/// "年度" is a single token. Days and months without a year are not extracted.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::date::DateExtractor;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+/// use tantivy::TantivyDocument;
+/// use tantivy::schema::{FAST, INDEXED, Schema, TEXT};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let date = schema_builder.add_date_field("date", INDEXED | FAST);
+///
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let extractor = DateExtractor::new(LinderaTokenizer::from_segmenter(segmenter));
+///
+/// let text = "令和5年3月1日に開業した";
+/// let mut document = TantivyDocument::default();
+/// document.add_text(body, text);
+/// extractor.add_to_document(&mut document, date, text)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DateExtractor {
+    tokenizer: LinderaTokenizer,
+}
+
+impl DateExtractor {
+    /// Creates a new `DateExtractor`.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer to analyze the text with
+    pub fn new(tokenizer: LinderaTokenizer) -> DateExtractor {
+        DateExtractor { tokenizer }
+    }
+
+    /// Extracts the dates mentioned in a text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to extract the dates of
+    ///
+    /// # Returns
+    ///
+    /// Returns the dates in the order of the text, or an error if the analysis fails.
+    pub fn extract(&self, text: &str) -> Result<Vec<ExtractedDate>> {
+        let tokens = self.tokenizer.analyze(text)?;
+        let token_ends: HashSet<usize> = tokens.iter().map(|token| token.byte_end).collect();
+
+        let mut dates = Vec::new();
+        let mut offset = 0;
+        for token in &tokens {
+            let byte_start = token.byte_start;
+            if byte_start < offset {
+                continue;
+            }
+            let Some((date, precision, len)) = match_date(&text[byte_start..], |len| {
+                token_ends.contains(&(byte_start + len))
+            }) else {
+                continue;
+            };
+
+            offset = byte_start + len;
+            dates.push(ExtractedDate {
+                date,
+                precision,
+                byte_start,
+                byte_end: offset,
+            });
+        }
+
+        Ok(dates)
+    }
+
+    /// Adds the dates mentioned in a text to a date field of a document.
+    ///
+    /// The field should be indexed or a fast field to be filtered by range. A document
+    /// mentioning several dates gets several values.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document to add the dates to
+    /// * `field` - The date field to add the dates to
+    /// * `text` - The text to extract the dates of
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of dates added, or an error if the analysis fails.
+    pub fn add_to_document(
+        &self,
+        document: &mut TantivyDocument,
+        field: Field,
+        text: &str,
+    ) -> Result<usize> {
+        let dates = self.extract(text)?;
+        for date in &dates {
+            document.add_date(field, date.date);
+        }
+
+        Ok(dates.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::DateTime;
+
+    use super::{DatePrecision, parse_date, to_date_time};
+
+    #[test]
+    fn test_to_date_time() {
+        assert_eq!(to_date_time(1970, 1, 1), DateTime::from_timestamp_secs(0));
+        assert_eq!(
+            to_date_time(2000, 3, 1),
+            DateTime::from_timestamp_secs(951_868_800)
+        );
+        assert_eq!(
+            to_date_time(1868, 1, 1),
+            DateTime::from_timestamp_secs(-3_218_832_000)
+        );
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let day = |year, month, day| Some((to_date_time(year, month, day), DatePrecision::Day));
+        assert_eq!(parse_date("2023年3月1日"), day(2023, 3, 1));
+        assert_eq!(parse_date("２０２３年３月１日"), day(2023, 3, 1));
+        assert_eq!(parse_date("令和5年3月1日"), day(2023, 3, 1));
+        assert_eq!(parse_date("令和元年五月一日"), day(2019, 5, 1));
+        assert_eq!(parse_date("平成三十一年四月三十日"), day(2019, 4, 30));
+        assert_eq!(parse_date("昭和64年1月7日"), day(1989, 1, 7));
+        assert_eq!(parse_date("明治45年7月29日"), day(1912, 7, 29));
+        assert_eq!(parse_date("二〇二四年二月二十九日"), day(2024, 2, 29));
+        assert_eq!(parse_date("二千二十三年三月一日"), day(2023, 3, 1));
+        assert_eq!(parse_date("千九百八十九年一月八日"), day(1989, 1, 8));
+        assert_eq!(parse_date("平成元年1月8日"), day(1989, 1, 8));
+
+        assert_eq!(
+            parse_date("2023年3月"),
+            Some((to_date_time(2023, 3, 1), DatePrecision::Month))
+        );
+        assert_eq!(
+            parse_date("平成元年"),
+            Some((to_date_time(1989, 1, 1), DatePrecision::Year))
+        );
+        assert_eq!(
+            parse_date("平成31年4月"),
+            Some((to_date_time(2019, 4, 1), DatePrecision::Month))
+        );
+        assert_eq!(
+            parse_date("令和100年"),
+            Some((to_date_time(2118, 1, 1), DatePrecision::Year))
+        );
+
+        assert_eq!(parse_date("3年"), None);
+        assert_eq!(parse_date("3月1日"), None);
+        assert_eq!(parse_date("2023年13月"), None);
+        assert_eq!(parse_date("2023年2月29日"), None);
+        assert_eq!(parse_date("0年"), None);
+        assert_eq!(parse_date("令和0年"), None);
+        assert_eq!(parse_date("令和〇年"), None);
+        assert_eq!(parse_date("平成32年"), None);
+        assert_eq!(parse_date("平成31年5月"), None);
+        assert_eq!(parse_date("平成元年1月7日"), None);
+        assert_eq!(parse_date("令和元年4月30日"), None);
+        assert_eq!(parse_date("昭和64年2月"), None);
+        assert_eq!(parse_date("平成99年"), None);
+        assert_eq!(parse_date("大正16年"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_date_range_search() {
+        use std::ops::Bound;
+
+        use tantivy::collector::Count;
+        use tantivy::query::RangeQuery;
+        use tantivy::schema::{FAST, INDEXED, Schema, TEXT};
+        use tantivy::{TantivyDocument, Term};
+
+        use crate::test_helpers::{create_index, ipadic_tokenizer};
+
+        use super::DateExtractor;
+
+        let extractor = DateExtractor::new(ipadic_tokenizer());
+
+        let dates = extractor
+            .extract("平成31年4月30日に退位し、2019年5月に即位した")
+            .unwrap();
+        assert_eq!(dates.len(), 2);
+        assert_eq!(dates[0].date, to_date_time(2019, 4, 30));
+        assert_eq!(dates[0].precision, DatePrecision::Day);
+        assert_eq!(dates[1].date, to_date_time(2019, 5, 1));
+        assert_eq!(dates[1].precision, DatePrecision::Month);
+
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let date = schema_builder.add_date_field("date", INDEXED | FAST);
+        let documents = ["令和5年3月1日に開業", "2010年に完成", "3年前に開業"].map(|text| {
+            let mut document = TantivyDocument::default();
+            document.add_text(body, text);
+            extractor
+                .add_to_document(&mut document, date, text)
+                .unwrap();
+            document
+        });
+        let index = create_index(schema_builder.build(), documents);
+        let searcher = index.reader().unwrap().searcher();
+
+        let range = |lower: DateTime, upper: DateTime| {
+            let query = RangeQuery::new(
+                Bound::Included(Term::from_field_date(date, lower)),
+                Bound::Excluded(Term::from_field_date(date, upper)),
+            );
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(range(to_date_time(2023, 1, 1), to_date_time(2024, 1, 1)), 1);
+        assert_eq!(range(to_date_time(2000, 1, 1), to_date_time(2030, 1, 1)), 2);
+    }
+}
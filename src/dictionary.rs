@@ -0,0 +1,227 @@
+//! Process-wide dictionary registry.
+//!
+//! This module provides a registry of the dictionaries the constructors of
+//! [`LinderaTokenizer`](crate::tokenizer::LinderaTokenizer) load, keyed by their URI or path,
+//! so that a dictionary is read, decompressed and deserialized once per process however many
+//! tokenizers are built from it. The registry hands out the dictionaries as `Arc`s, so the
+//! tokenizers built from the same dictionary, and their clones, share one copy of it.
+//!
+//! A dictionary stays in the registry until it is evicted, and it can only be evicted once
+//! no tokenizer uses it anymore.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex, MutexGuard, PoisonError};
+
+use tantivy::Result;
+use tantivy::TantivyError;
+
+use lindera::LinderaResult;
+use lindera::dictionary::Dictionary;
+
+static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::default);
+
+/// A dictionary in the registry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoadedDictionary {
+    /// The URI or path the dictionary was loaded from.
+    pub uri: String,
+    /// Whether the dictionary is memory-mapped.
+    pub mmap: bool,
+    /// The number of tokenizers (and clones of tokenizers) using the dictionary.
+    pub references: usize,
+}
+
+/// The registered dictionaries, keyed by their URI or path and whether they are
+/// memory-mapped.
+type Dictionaries = HashMap<(String, bool), Arc<Dictionary>>;
+
+#[derive(Default)]
+struct Registry {
+    dictionaries: Mutex<Dictionaries>,
+}
+
+impl Registry {
+    fn dictionaries(&self) -> MutexGuard<'_, Dictionaries> {
+        // The map is never left in an inconsistent state, so a poisoned lock can be reused.
+        self.dictionaries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn load(
+        &self,
+        key: (String, bool),
+        loader: impl FnOnce() -> LinderaResult<Dictionary>,
+    ) -> Result<Arc<Dictionary>> {
+        // The lock is held while loading, so that concurrent callers load a dictionary once.
+        let mut dictionaries = self.dictionaries();
+        if let Some(dictionary) = dictionaries.get(&key) {
+            return Ok(Arc::clone(dictionary));
+        }
+
+        let dictionary = loader().map_err(|e| TantivyError::InvalidArgument(format!("{e:?}")))?;
+        let dictionary = Arc::new(dictionary);
+        dictionaries.insert(key, Arc::clone(&dictionary));
+
+        Ok(dictionary)
+    }
+
+    fn loaded(&self) -> Vec<LoadedDictionary> {
+        let mut loaded: Vec<LoadedDictionary> = self
+            .dictionaries()
+            .iter()
+            .map(|((uri, mmap), dictionary)| LoadedDictionary {
+                uri: uri.clone(),
+                mmap: *mmap,
+                // The registry holds one of the references.
+                references: Arc::strong_count(dictionary) - 1,
+            })
+            .collect();
+        loaded.sort_by(|a, b| (&a.uri, a.mmap).cmp(&(&b.uri, b.mmap)));
+
+        loaded
+    }
+
+    fn evict(&self, uri: &str) -> bool {
+        let mut dictionaries = self.dictionaries();
+        let len = dictionaries.len();
+        dictionaries.retain(|(key, _), dictionary| key != uri || Arc::strong_count(dictionary) > 1);
+
+        dictionaries.len() < len
+    }
+
+    fn evict_unused(&self) -> usize {
+        let mut dictionaries = self.dictionaries();
+        let len = dictionaries.len();
+        dictionaries.retain(|_, dictionary| Arc::strong_count(dictionary) > 1);
+
+        len - dictionaries.len()
+    }
+}
+
+/// Returns the dictionary of a URI from the registry, loading it on first use.
+///
+/// # Arguments
+///
+/// * `uri` - The URI or path of the dictionary, as accepted by
+///   `lindera::dictionary::load_dictionary`, e.g. "embedded://ipadic"
+///
+/// # Returns
+///
+/// Returns the shared dictionary, or an error if it cannot be loaded.
+pub fn load_dictionary(uri: &str) -> Result<Arc<Dictionary>> {
+    REGISTRY.load((uri.to_string(), false), || {
+        lindera::dictionary::load_dictionary(uri)
+    })
+}
+
+/// Returns the memory-mapped dictionary of a compiled dictionary directory from the
+/// registry, loading it on first use.
+///
+/// # Arguments
+///
+/// * `path` - Path to the compiled dictionary directory
+///
+/// # Returns
+///
+/// Returns the shared dictionary, or an error if it cannot be loaded.
+pub fn load_mmap_dictionary(path: &Path) -> Result<Arc<Dictionary>> {
+    REGISTRY.load((path.display().to_string(), true), || {
+        Dictionary::load_from_path_with_options(path, true)
+    })
+}
+
+/// Returns the dictionaries in the registry, sorted by URI.
+pub fn loaded_dictionaries() -> Vec<LoadedDictionary> {
+    REGISTRY.loaded()
+}
+
+/// Removes the dictionaries of a URI or path from the registry if no tokenizer uses them,
+/// freeing their memory.
+///
+/// # Arguments
+///
+/// * `uri` - The URI or path of the dictionary
+///
+/// # Returns
+///
+/// Returns `true` if a dictionary was removed.
+pub fn evict(uri: &str) -> bool {
+    REGISTRY.evict(uri)
+}
+
+/// Removes the dictionaries no tokenizer uses from the registry, freeing their memory.
+///
+/// # Returns
+///
+/// Returns the number of dictionaries removed.
+pub fn evict_unused() -> usize {
+    REGISTRY.evict_unused()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Registry;
+
+    #[test]
+    fn test_load_dictionary_error() {
+        let registry = Registry::default();
+        let uri = "/nonexistent/dictionary";
+        assert!(
+            registry
+                .load((uri.to_string(), false), || {
+                    lindera::dictionary::load_dictionary(uri)
+                })
+                .is_err()
+        );
+        assert!(registry.loaded().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_load_dictionary_once() {
+        use std::cell::Cell;
+        use std::sync::Arc;
+
+        use super::LoadedDictionary;
+
+        let registry = Registry::default();
+        let uri = "embedded://ipadic";
+        let loads = Cell::new(0);
+        let load = || {
+            registry
+                .load((uri.to_string(), false), || {
+                    loads.set(loads.get() + 1);
+                    lindera::dictionary::load_dictionary(uri)
+                })
+                .unwrap()
+        };
+
+        let first = load();
+        let second = load();
+        assert_eq!(loads.get(), 1);
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(
+            registry.loaded(),
+            vec![LoadedDictionary {
+                uri: uri.to_string(),
+                mmap: false,
+                references: 2,
+            }]
+        );
+
+        // A dictionary in use is not evicted.
+        assert!(!registry.evict(uri));
+        drop(first);
+        assert_eq!(registry.evict_unused(), 0);
+        drop(second);
+        assert_eq!(registry.evict_unused(), 1);
+        assert!(registry.loaded().is_empty());
+
+        drop(load());
+        assert_eq!(loads.get(), 2);
+        assert!(registry.evict(uri));
+        assert!(!registry.evict(uri));
+    }
+}
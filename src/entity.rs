@@ -0,0 +1,278 @@
+//! Entity extraction into facets.
+//!
+//! This module provides the [`EntityExtractor`], which uses the proper-noun tags of the
+//! dictionary as a cheap named entity recognizer, and adds the entities mentioned in a
+//! text to a facet field, e.g. `/place/東京` or `/person/山田太郎`.
+
+use std::collections::{HashMap, HashSet};
+
+use tantivy::Result;
+use tantivy::TantivyDocument;
+use tantivy::schema::{Facet, Field};
+
+use crate::pos::{matches_tag, pos_tag};
+use crate::tokenizer::LinderaTokenizer;
+
+/// An entity extracted from a text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entity {
+    /// The text of the entity.
+    pub text: String,
+    /// The facet path of the category of the entity, e.g. "/place".
+    pub category: String,
+    /// The byte offset of the start of the entity in the text.
+    pub byte_start: usize,
+    /// The byte offset of the end of the entity in the text.
+    pub byte_end: usize,
+}
+
+impl Entity {
+    /// Returns the facet of the entity, made of its category followed by its text.
+    pub fn facet(&self) -> Facet {
+        Facet::from_path(
+            self.category
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .chain([self.text.as_str()]),
+        )
+    }
+}
+
+/// An extractor of the entities mentioned in a text.
+///
+/// The text is analyzed with the given [`LinderaTokenizer`], and each token whose
+/// part-of-speech tag matches one of the tags of the categories is part of an entity of
+/// that category; tags match their subcategories, and the most specific matching tag
+/// wins. Adjacent tokens of the same category are merged into one entity, so "山田"
+/// (`名詞,固有名詞,人名,姓`) followed by "太郎" (`名詞,固有名詞,人名,名`) is extracted as the
+/// single person "山田太郎".
+///
+/// The categories default to [`ipadic_categories`](Self::ipadic_categories); since the
+/// tagsets differ between dictionaries, [`unidic_categories`](Self::unidic_categories) is
+/// provided for UniDic.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::entity::EntityExtractor;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+/// use tantivy::TantivyDocument;
+/// use tantivy::schema::{FacetOptions, Schema, TEXT};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let entities = schema_builder.add_facet_field("entities", FacetOptions::default());
+///
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let extractor = EntityExtractor::new(LinderaTokenizer::from_segmenter(segmenter));
+///
+/// // Adds the facets "/person/山田太郎" and "/place/東京".
+/// let text = "山田太郎は東京に住んでいる";
+/// let mut document = TantivyDocument::default();
+/// document.add_text(body, text);
+/// extractor.add_to_document(&mut document, entities, text)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct EntityExtractor {
+    tokenizer: LinderaTokenizer,
+    categories: HashMap<String, String>,
+}
+
+impl EntityExtractor {
+    /// Creates a new `EntityExtractor` with the categories of IPADIC.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer to analyze the text with
+    pub fn new(tokenizer: LinderaTokenizer) -> EntityExtractor {
+        EntityExtractor {
+            tokenizer,
+            categories: Self::ipadic_categories(),
+        }
+    }
+
+    /// Returns the categories of the proper nouns of IPADIC: places, persons and
+    /// organizations.
+    pub fn ipadic_categories() -> HashMap<String, String> {
+        HashMap::from([
+            ("名詞,固有名詞,地域".to_string(), "/place".to_string()),
+            ("名詞,固有名詞,人名".to_string(), "/person".to_string()),
+            (
+                "名詞,固有名詞,組織".to_string(),
+                "/organization".to_string(),
+            ),
+        ])
+    }
+
+    /// Returns the categories of the proper nouns of UniDic: places and persons.
+    pub fn unidic_categories() -> HashMap<String, String> {
+        HashMap::from([
+            ("名詞,固有名詞,地名".to_string(), "/place".to_string()),
+            ("名詞,固有名詞,人名".to_string(), "/person".to_string()),
+        ])
+    }
+
+    /// Sets the categories of the entities.
+    ///
+    /// # Arguments
+    ///
+    /// * `categories` - A map from part-of-speech tags to the facet paths of the
+    ///   categories, e.g. "名詞,固有名詞,地域" to "/place"
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_categories(&mut self, categories: HashMap<String, String>) -> &mut Self {
+        self.categories = categories;
+
+        self
+    }
+
+    /// Returns the category of a part-of-speech tag, from its most specific matching tag.
+    fn category(&self, pos_tag: &str) -> Option<&str> {
+        self.categories
+            .iter()
+            .filter(|(tag, _)| matches_tag(pos_tag, tag))
+            .max_by_key(|(tag, _)| tag.len())
+            .map(|(_, category)| category.as_str())
+    }
+
+    /// Extracts the entities mentioned in a text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to extract the entities of
+    ///
+    /// # Returns
+    ///
+    /// Returns the entities in the order of the text, or an error if the analysis fails.
+    pub fn extract(&self, text: &str) -> Result<Vec<Entity>> {
+        let mut entities: Vec<Entity> = Vec::new();
+        let mut merging = false;
+
+        for mut token in self.tokenizer.analyze(text)? {
+            let Some(category) = self.category(&pos_tag(&mut token)) else {
+                merging = false;
+                continue;
+            };
+
+            match entities.last_mut() {
+                Some(entity)
+                    if merging
+                        && entity.category == category
+                        && entity.byte_end == token.byte_start =>
+                {
+                    entity.text.push_str(&token.surface);
+                    entity.byte_end = token.byte_end;
+                }
+                _ => entities.push(Entity {
+                    text: token.surface.to_string(),
+                    category: category.to_string(),
+                    byte_start: token.byte_start,
+                    byte_end: token.byte_end,
+                }),
+            }
+            merging = true;
+        }
+
+        Ok(entities)
+    }
+
+    /// Adds the facets of the entities mentioned in a text to a facet field of a document.
+    ///
+    /// An entity mentioned several times is added once.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document to add the facets to
+    /// * `field` - The facet field to add the facets to
+    /// * `text` - The text to extract the entities of
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of facets added, or an error if the analysis fails.
+    pub fn add_to_document(
+        &self,
+        document: &mut TantivyDocument,
+        field: Field,
+        text: &str,
+    ) -> Result<usize> {
+        let mut facets = HashSet::new();
+        for entity in self.extract(text)? {
+            let facet = entity.facet();
+            if facets.insert(facet.clone()) {
+                document.add_facet(field, facet);
+            }
+        }
+
+        Ok(facets.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Entity;
+
+    #[test]
+    fn test_entity_facet() {
+        let entity = Entity {
+            text: "東京/大阪".to_string(),
+            category: "/place/city".to_string(),
+            byte_start: 0,
+            byte_end: 13,
+        };
+        assert_eq!(entity.facet().to_path(), vec!["place", "city", "東京/大阪"]);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_entity_facet_search() {
+        use tantivy::TantivyDocument;
+        use tantivy::collector::FacetCollector;
+        use tantivy::query::AllQuery;
+        use tantivy::schema::{FacetOptions, Schema, TEXT};
+
+        use crate::test_helpers::{create_index, ipadic_tokenizer};
+
+        use super::EntityExtractor;
+
+        let extractor = EntityExtractor::new(ipadic_tokenizer());
+
+        let entities = extractor.extract("山田太郎は東京に住んでいる").unwrap();
+        let facets: Vec<String> = entities
+            .iter()
+            .map(|entity| entity.facet().to_string())
+            .collect();
+        assert_eq!(facets, vec!["/person/山田太郎", "/place/東京"]);
+
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let facet_field = schema_builder.add_facet_field("entities", FacetOptions::default());
+        let documents = ["山田太郎は東京に住んでいる", "東京から大阪へ行く"].map(|text| {
+            let mut document = TantivyDocument::default();
+            document.add_text(body, text);
+            extractor
+                .add_to_document(&mut document, facet_field, text)
+                .unwrap();
+            document
+        });
+        let index = create_index(schema_builder.build(), documents);
+        let searcher = index.reader().unwrap().searcher();
+
+        let mut facet_collector = FacetCollector::for_field("entities");
+        facet_collector.add_facet("/place");
+        let facet_counts = searcher.search(&AllQuery, &facet_collector).unwrap();
+        let places: Vec<(String, u64)> = facet_counts
+            .get("/place")
+            .map(|(facet, count)| (facet.to_string(), count))
+            .collect();
+        assert!(places.contains(&("/place/東京".to_string(), 2)));
+    }
+}
@@ -0,0 +1,248 @@
+//! Keyword extraction.
+//!
+//! This module provides the [`KeywordExtractor`], which picks the keywords of a text by
+//! combining the part-of-speech tags of Lindera with the document frequencies of a Tantivy
+//! index.
+
+use std::collections::{HashMap, HashSet};
+
+use tantivy::Result;
+use tantivy::Score;
+use tantivy::Searcher;
+use tantivy::Term;
+use tantivy::schema::Field;
+
+use crate::pos::{matches_tag, pos_tag};
+use crate::tokenizer::LinderaTokenizer;
+
+/// A keyword extracted from a text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyword {
+    /// The text of the keyword, as it is indexed.
+    pub text: String,
+    /// The TF-IDF score of the keyword.
+    pub score: Score,
+    /// The number of occurrences of the keyword in the text.
+    pub term_frequency: u32,
+    /// The number of documents of the index containing the keyword.
+    pub doc_frequency: u64,
+}
+
+/// An extractor of the keywords of a text.
+///
+/// The text is analyzed with the given [`LinderaTokenizer`], which should be the tokenizer
+/// of the field, so the candidates are the terms the field would be indexed with. Only the
+/// tokens whose part-of-speech tag matches one of the keep tags are candidates; tags match
+/// their subcategories, so "名詞" keeps every noun. The candidates are ranked by TF-IDF,
+/// where the term frequency is counted in the text and the inverse document frequency is
+/// computed from the document frequencies of the field in the index.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::HashSet;
+///
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::keyword::KeywordExtractor;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # #[cfg(feature = "embed-ipadic")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # use tantivy::Index;
+/// # use tantivy::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+/// # let mut schema_builder = Schema::builder();
+/// # let body = schema_builder.add_text_field(
+/// #     "body",
+/// #     TextOptions::default().set_indexing_options(
+/// #         TextFieldIndexing::default()
+/// #             .set_tokenizer("lang_ja")
+/// #             .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+/// #     ),
+/// # );
+/// # let index = Index::create_in_ram(schema_builder.build());
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+/// # index.tokenizers().register("lang_ja", tokenizer.clone());
+///
+/// let mut extractor = KeywordExtractor::new(tokenizer, body);
+/// extractor
+///     .set_keep_tags(HashSet::from(["名詞".to_string()]))
+///     .set_limit(5);
+///
+/// let searcher = index.reader()?.searcher();
+/// for keyword in extractor.extract(&searcher, "関西国際空港から東京国際空港へ向かう")? {
+///     println!("{} {}", keyword.text, keyword.score);
+/// }
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "embed-ipadic"))]
+/// # fn main() {}
+/// ```
+#[derive(Clone)]
+pub struct KeywordExtractor {
+    tokenizer: LinderaTokenizer,
+    field: Field,
+    keep_tags: HashSet<String>,
+    limit: usize,
+}
+
+impl KeywordExtractor {
+    /// Creates a new `KeywordExtractor` that keeps all tokens and returns up to 10 keywords.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer to analyze the text with, which should match the
+    ///   tokenizer the field is indexed with
+    /// * `field` - The field whose document frequencies are used
+    pub fn new(tokenizer: LinderaTokenizer, field: Field) -> KeywordExtractor {
+        KeywordExtractor {
+            tokenizer,
+            field,
+            keep_tags: HashSet::new(),
+            limit: 10,
+        }
+    }
+
+    /// Sets the part-of-speech tags of the tokens that can be keywords.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep_tags` - The tags to keep, e.g. "名詞" to keep nouns; all tokens are kept if
+    ///   empty
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_keep_tags(&mut self, keep_tags: HashSet<String>) -> &mut Self {
+        self.keep_tags = keep_tags;
+
+        self
+    }
+
+    /// Sets the maximum number of keywords to return.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum number of keywords
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_limit(&mut self, limit: usize) -> &mut Self {
+        self.limit = limit;
+
+        self
+    }
+
+    /// Extracts the keywords of a text.
+    ///
+    /// # Arguments
+    ///
+    /// * `searcher` - The searcher of the index providing the document frequencies
+    /// * `text` - The text to extract the keywords of
+    ///
+    /// # Returns
+    ///
+    /// Returns the keywords ordered by decreasing score, or an error if the analysis or
+    /// the lookup of the document frequencies fails.
+    pub fn extract(&self, searcher: &Searcher, text: &str) -> Result<Vec<Keyword>> {
+        let num_docs = searcher.num_docs();
+
+        let mut keywords = Vec::new();
+        for (text, term_frequency) in term_frequencies(&self.tokenizer, &self.keep_tags, text)? {
+            let doc_frequency = searcher.doc_freq(&Term::from_field_text(self.field, &text))?;
+            let score = term_frequency as Score * idf(doc_frequency, num_docs);
+            keywords.push(Keyword {
+                text,
+                score,
+                term_frequency,
+                doc_frequency,
+            });
+        }
+
+        keywords.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.text.cmp(&b.text))
+        });
+        keywords.truncate(self.limit);
+
+        Ok(keywords)
+    }
+}
+
+/// Counts the occurrences of the terms of a text whose tokens match one of the keep tags,
+/// or of all its terms if there is no keep tag.
+pub(crate) fn term_frequencies(
+    tokenizer: &LinderaTokenizer,
+    keep_tags: &HashSet<String>,
+    text: &str,
+) -> Result<HashMap<String, u32>> {
+    let mut term_frequencies: HashMap<String, u32> = HashMap::new();
+    for mut token in tokenizer.analyze(text)? {
+        if !keep_tags.is_empty() {
+            let pos_tag = pos_tag(&mut token);
+            if !keep_tags.iter().any(|tag| matches_tag(&pos_tag, tag)) {
+                continue;
+            }
+        }
+        *term_frequencies
+            .entry(token.surface.into_owned())
+            .or_default() += 1;
+    }
+    Ok(term_frequencies)
+}
+
+/// Returns the smoothed inverse document frequency of a term.
+pub(crate) fn idf(doc_frequency: u64, num_docs: u64) -> Score {
+    ((num_docs as Score + 1.0) / (doc_frequency as Score + 1.0)).ln() + 1.0
+}
+
+#[cfg(test)]
+#[cfg(feature = "embed-ipadic")]
+mod tests {
+    use std::collections::HashSet;
+
+    use tantivy::doc;
+    use tantivy::schema::Schema;
+
+    use crate::test_helpers::{create_index, ipadic_tokenizer, text_options};
+
+    use super::KeywordExtractor;
+
+    #[test]
+    fn test_extract_keywords() {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", text_options());
+        let documents = (0..10)
+            .map(|_| doc!(body => "東京に行く"))
+            .chain([doc!(body => "東京の空港")]);
+        let index = create_index(schema_builder.build(), documents);
+        let searcher = index.reader().unwrap().searcher();
+
+        let mut extractor = KeywordExtractor::new(ipadic_tokenizer(), body);
+        extractor.set_keep_tags(HashSet::from(["名詞".to_string()]));
+
+        let keywords = extractor.extract(&searcher, "東京の空港と東京").unwrap();
+        let texts: Vec<&str> = keywords
+            .iter()
+            .map(|keyword| keyword.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["空港", "東京"]);
+        assert_eq!(keywords[0].doc_frequency, 1);
+        assert_eq!(keywords[1].term_frequency, 2);
+        assert_eq!(keywords[1].doc_frequency, 11);
+
+        extractor.set_limit(1);
+        assert_eq!(
+            extractor
+                .extract(&searcher, "東京の空港と東京")
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -100,5 +100,23 @@
 //! # }
 //! ```
 
+pub mod adapter;
+pub mod analyzer;
+pub mod autocomplete;
+pub mod date;
+pub mod dictionary;
+pub mod entity;
+pub mod keyword;
+pub mod more_like_this;
+mod pos;
+pub mod quantity;
+pub mod query;
+pub mod romaji;
+mod segmenter;
 pub mod stream;
+pub mod suggest;
+#[cfg(test)]
+#[cfg(any(feature = "embed-ipadic", feature = "embed-ko-dic"))]
+mod test_helpers;
+pub mod token_filter;
 pub mod tokenizer;
@@ -0,0 +1,300 @@
+//! More-like-this queries from raw text.
+//!
+//! This module provides the [`MoreLikeThisBuilder`], which builds a query finding the
+//! documents similar to a text that is not indexed, analyzing it with Lindera instead of
+//! reading the terms of an indexed document as Tantivy's `MoreLikeThisQuery` does.
+
+use std::collections::HashSet;
+
+use tantivy::Result;
+use tantivy::Score;
+use tantivy::Searcher;
+use tantivy::Term;
+use tantivy::query::{BooleanQuery, BoostQuery, Occur, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+
+use crate::keyword::{idf, term_frequencies};
+use crate::tokenizer::LinderaTokenizer;
+
+/// A builder of queries finding the documents similar to a raw text.
+///
+/// The text is analyzed with the given [`LinderaTokenizer`], which should be the tokenizer
+/// of the field, and only the tokens whose part-of-speech tag matches one of the keep tags
+/// are kept; tags match their subcategories. As in Tantivy's `MoreLikeThisQuery`, the terms
+/// occurring less than the minimum term frequency in the text, or in fewer or more
+/// documents than the document frequency bounds, are discarded, the remaining terms are
+/// ranked by TF-IDF, and the best ones are combined in a disjunction. The defaults are
+/// those of Tantivy's `MoreLikeThisQuery`.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::collections::HashSet;
+///
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::more_like_this::MoreLikeThisBuilder;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+/// use tantivy::collector::TopDocs;
+///
+/// # #[cfg(feature = "embed-ipadic")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # use tantivy::Index;
+/// # use tantivy::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+/// # let mut schema_builder = Schema::builder();
+/// # let body = schema_builder.add_text_field(
+/// #     "body",
+/// #     TextOptions::default().set_indexing_options(
+/// #         TextFieldIndexing::default()
+/// #             .set_tokenizer("lang_ja")
+/// #             .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+/// #     ),
+/// # );
+/// # let index = Index::create_in_ram(schema_builder.build());
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+/// # index.tokenizers().register("lang_ja", tokenizer.clone());
+///
+/// let mut builder = MoreLikeThisBuilder::new(tokenizer, body);
+/// builder
+///     .set_keep_tags(HashSet::from(["名詞".to_string()]))
+///     .set_min_doc_frequency(1)
+///     .set_min_term_frequency(1)
+///     .set_max_query_terms(10);
+///
+/// let searcher = index.reader()?.searcher();
+/// let query = builder.build(&searcher, "関西国際空港の新しいターミナルが開業した")?;
+/// let similar_docs = searcher.search(&query, &TopDocs::with_limit(10))?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "embed-ipadic"))]
+/// # fn main() {}
+/// ```
+#[derive(Clone)]
+pub struct MoreLikeThisBuilder {
+    tokenizer: LinderaTokenizer,
+    field: Field,
+    keep_tags: HashSet<String>,
+    min_doc_frequency: u64,
+    max_doc_frequency: Option<u64>,
+    min_term_frequency: u32,
+    max_query_terms: usize,
+    boost_factor: Option<Score>,
+}
+
+impl MoreLikeThisBuilder {
+    /// Creates a new `MoreLikeThisBuilder`.
+    ///
+    /// It keeps all tokens, discards the terms occurring less than twice in the text or in
+    /// fewer than 5 documents, keeps up to 25 terms, and boosts each term by its score.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer to analyze the text with, which should match the
+    ///   tokenizer the field is indexed with
+    /// * `field` - The field to search
+    pub fn new(tokenizer: LinderaTokenizer, field: Field) -> MoreLikeThisBuilder {
+        MoreLikeThisBuilder {
+            tokenizer,
+            field,
+            keep_tags: HashSet::new(),
+            min_doc_frequency: 5,
+            max_doc_frequency: None,
+            min_term_frequency: 2,
+            max_query_terms: 25,
+            boost_factor: Some(1.0),
+        }
+    }
+
+    /// Sets the part-of-speech tags of the tokens to keep.
+    ///
+    /// # Arguments
+    ///
+    /// * `keep_tags` - The tags to keep, e.g. "名詞" to keep nouns; all tokens are kept if
+    ///   empty
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_keep_tags(&mut self, keep_tags: HashSet<String>) -> &mut Self {
+        self.keep_tags = keep_tags;
+
+        self
+    }
+
+    /// Sets the minimum number of documents a term must occur in.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_doc_frequency` - The minimum document frequency
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_min_doc_frequency(&mut self, min_doc_frequency: u64) -> &mut Self {
+        self.min_doc_frequency = min_doc_frequency;
+
+        self
+    }
+
+    /// Sets the maximum number of documents a term may occur in.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_doc_frequency` - The maximum document frequency
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_max_doc_frequency(&mut self, max_doc_frequency: u64) -> &mut Self {
+        self.max_doc_frequency = Some(max_doc_frequency);
+
+        self
+    }
+
+    /// Sets the minimum number of times a term must occur in the text.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_term_frequency` - The minimum term frequency
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_min_term_frequency(&mut self, min_term_frequency: u32) -> &mut Self {
+        self.min_term_frequency = min_term_frequency;
+
+        self
+    }
+
+    /// Sets the maximum number of terms in the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_query_terms` - The maximum number of terms
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_max_query_terms(&mut self, max_query_terms: usize) -> &mut Self {
+        self.max_query_terms = max_query_terms;
+
+        self
+    }
+
+    /// Sets the factor the score of each term is multiplied by to boost it.
+    ///
+    /// # Arguments
+    ///
+    /// * `boost_factor` - The boost factor, or `None` not to boost the terms
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_boost_factor(&mut self, boost_factor: Option<Score>) -> &mut Self {
+        self.boost_factor = boost_factor;
+
+        self
+    }
+
+    /// Builds the query finding the documents similar to a text.
+    ///
+    /// # Arguments
+    ///
+    /// * `searcher` - The searcher of the index providing the document frequencies
+    /// * `text` - The text to find similar documents to
+    ///
+    /// # Returns
+    ///
+    /// Returns the query, which matches nothing if no term is left, or an error if the
+    /// analysis or the lookup of the document frequencies fails.
+    pub fn build(&self, searcher: &Searcher, text: &str) -> Result<Box<dyn Query>> {
+        let num_docs = searcher.num_docs();
+
+        let mut scored_terms: Vec<(Score, Term)> = Vec::new();
+        for (text, term_frequency) in term_frequencies(&self.tokenizer, &self.keep_tags, text)? {
+            if term_frequency < self.min_term_frequency {
+                continue;
+            }
+            let term = Term::from_field_text(self.field, &text);
+            let doc_frequency = searcher.doc_freq(&term)?;
+            if doc_frequency == 0
+                || doc_frequency < self.min_doc_frequency
+                || self
+                    .max_doc_frequency
+                    .is_some_and(|max_doc_frequency| doc_frequency > max_doc_frequency)
+            {
+                continue;
+            }
+            let score = term_frequency as Score * idf(doc_frequency, num_docs);
+            scored_terms.push((score, term));
+        }
+
+        scored_terms
+            .sort_by(|(a, a_term), (b, b_term)| b.total_cmp(a).then_with(|| a_term.cmp(b_term)));
+        scored_terms.truncate(self.max_query_terms);
+
+        let subqueries = scored_terms
+            .into_iter()
+            .map(|(score, term)| {
+                let mut query: Box<dyn Query> =
+                    Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+                if let Some(boost_factor) = self.boost_factor {
+                    query = Box::new(BoostQuery::new(query, score * boost_factor));
+                }
+                (Occur::Should, query)
+            })
+            .collect();
+
+        Ok(Box::new(BooleanQuery::new(subqueries)))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "embed-ipadic")]
+mod tests {
+    use std::collections::HashSet;
+
+    use tantivy::collector::TopDocs;
+    use tantivy::doc;
+    use tantivy::schema::Schema;
+
+    use crate::test_helpers::{create_index, ipadic_tokenizer, text_options};
+
+    use super::MoreLikeThisBuilder;
+
+    #[test]
+    fn test_more_like_this() {
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", text_options());
+        let documents =
+            ["東京に行く", "東京の空港", "大阪に行く", "関西の空港"].map(|text| doc!(body => text));
+        let index = create_index(schema_builder.build(), documents);
+        let searcher = index.reader().unwrap().searcher();
+
+        let mut builder = MoreLikeThisBuilder::new(ipadic_tokenizer(), body);
+        builder
+            .set_keep_tags(HashSet::from(["名詞".to_string()]))
+            .set_min_doc_frequency(1)
+            .set_min_term_frequency(1);
+        let search = |builder: &MoreLikeThisBuilder, text: &str| -> Vec<u32> {
+            let query = builder.build(&searcher, text).unwrap();
+            searcher
+                .search(&query, &TopDocs::with_limit(10))
+                .unwrap()
+                .into_iter()
+                .map(|(_, address)| address.doc_id)
+                .collect()
+        };
+
+        // "行く" is not a noun, so only "東京" and "空港" are searched.
+        let docs = search(&builder, "東京の空港に行く");
+        assert_eq!(docs.len(), 3);
+        assert_eq!(docs[0], 1);
+
+        builder.set_max_query_terms(1).set_max_doc_frequency(1);
+        assert_eq!(search(&builder, "大阪と東京の空港"), vec![2]);
+    }
+}
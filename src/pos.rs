@@ -0,0 +1,46 @@
+//! Part-of-speech tags of Lindera tokens.
+//!
+//! This module provides the part-of-speech helpers shared by the [`query`](crate::query),
+//! [`keyword`](crate::keyword) and [`entity`](crate::entity) modules.
+
+use lindera::token::Token as LToken;
+
+/// Returns the part-of-speech tag of a token.
+///
+/// The tag is made of the first four details of the token separated by commas, which is
+/// the part-of-speech of IPADIC and UniDic, and starts with the part-of-speech of ko-dic
+/// and CC-CEDICT.
+pub(crate) fn pos_tag(token: &mut LToken<'_>) -> String {
+    token
+        .details()
+        .into_iter()
+        .take(4)
+        .collect::<Vec<&str>>()
+        .join(",")
+}
+
+/// Returns whether a part-of-speech tag is the given tag or one of its subcategories, e.g.
+/// "名詞,固有名詞,地域,一般" matches "名詞" and "名詞,固有名詞".
+pub(crate) fn matches_tag(pos_tag: &str, tag: &str) -> bool {
+    match pos_tag.strip_prefix(tag) {
+        Some(rest) => rest.is_empty() || rest.starts_with(','),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches_tag;
+
+    #[test]
+    fn test_matches_tag() {
+        assert!(matches_tag("名詞,固有名詞,地域,一般", "名詞"));
+        assert!(matches_tag("名詞,固有名詞,地域,一般", "名詞,固有名詞"));
+        assert!(matches_tag(
+            "名詞,固有名詞,地域,一般",
+            "名詞,固有名詞,地域,一般"
+        ));
+        assert!(!matches_tag("名詞,固有名詞,地域,一般", "名詞,一般"));
+        assert!(!matches_tag("名詞接続", "名詞"));
+    }
+}
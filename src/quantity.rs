@@ -0,0 +1,414 @@
+//! Numeric quantity extraction.
+//!
+//! This module provides the [`QuantityExtractor`], which extracts the quantities mentioned
+//! in a text, such as "1,980円" or "三千個", and adds their values to a numeric fast field,
+//! so that documents can be filtered by range on the prices or amounts in their text.
+
+use std::collections::HashSet;
+
+use tantivy::Result;
+use tantivy::TantivyDocument;
+use tantivy::schema::Field;
+
+use crate::tokenizer::LinderaTokenizer;
+
+/// The units recognized by default.
+const DEFAULT_UNITS: [&str; 18] = [
+    "円", "ドル", "個", "本", "枚", "冊", "台", "人", "件", "回", "歳", "%", "km", "m", "cm", "mm",
+    "kg", "g",
+];
+
+/// The distance between a full-width ASCII character and the ASCII character in Unicode.
+const FULL_WIDTH_OFFSET: u32 = 0xFEE0;
+
+/// A quantity extracted from a text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quantity {
+    /// The numeric value of the quantity.
+    pub value: f64,
+    /// The unit of the quantity, with full-width ASCII converted to half-width.
+    pub unit: String,
+    /// The byte offset of the start of the quantity in the text.
+    pub byte_start: usize,
+    /// The byte offset of the end of the quantity in the text, including the unit.
+    pub byte_end: usize,
+}
+
+/// Converts full-width ASCII characters to half-width.
+fn narrow(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '！'..='～' => char::from_u32(c as u32 - FULL_WIDTH_OFFSET).unwrap_or(c),
+            _ => c,
+        })
+        .collect()
+}
+
+/// Returns the value of a digit in Arabic or kanji numerals.
+fn digit(c: char) -> Option<f64> {
+    match c {
+        '0'..='9' => c.to_digit(10).map(f64::from),
+        '〇' | '零' => Some(0.0),
+        '一' => Some(1.0),
+        '二' => Some(2.0),
+        '三' => Some(3.0),
+        '四' => Some(4.0),
+        '五' => Some(5.0),
+        '六' => Some(6.0),
+        '七' => Some(7.0),
+        '八' => Some(8.0),
+        '九' => Some(9.0),
+        _ => None,
+    }
+}
+
+/// Returns the multiplier of a kanji numeral unit below ten thousand.
+fn small_unit(c: char) -> Option<f64> {
+    match c {
+        '十' => Some(10.0),
+        '百' => Some(100.0),
+        '千' => Some(1_000.0),
+        _ => None,
+    }
+}
+
+/// Returns the multiplier of a kanji numeral unit from ten thousand.
+fn large_unit(c: char) -> Option<f64> {
+    match c {
+        '万' => Some(1e4),
+        '億' => Some(1e8),
+        '兆' => Some(1e12),
+        _ => None,
+    }
+}
+
+/// Returns whether a character can be part of a number.
+fn is_number_char(c: char) -> bool {
+    digit(c).is_some()
+        || small_unit(c).is_some()
+        || large_unit(c).is_some()
+        || matches!(c, '.' | ',')
+}
+
+/// Returns whether the characters after a thousands separator start with exactly three
+/// digits.
+fn is_thousands_group(rest: &[char]) -> bool {
+    rest.len() >= 3
+        && rest[..3].iter().all(char::is_ascii_digit)
+        && !rest.get(3).is_some_and(char::is_ascii_digit)
+}
+
+/// Parses a number written in Arabic numerals, kanji numerals, or a mix of both.
+///
+/// Full-width digits, thousands separators, decimal points, and the kanji units "十", "百",
+/// "千", "万", "億" and "兆" are supported. A thousands separator must be followed by exactly
+/// three digits, so "1,2" is not a number. Unlike "十", "百" and "千", the units from "万" up
+/// must follow a numeral, so "万" alone, as in "万が一", is not a number.
+///
+/// # Arguments
+///
+/// * `text` - The number
+///
+/// # Returns
+///
+/// Returns the value of the number, or `None` if the text is not a number.
+///
+/// # Examples
+///
+/// ```
+/// use lindera_tantivy::quantity::parse_number;
+///
+/// assert_eq!(parse_number("1,980"), Some(1980.0));
+/// assert_eq!(parse_number("三千五百"), Some(3500.0));
+/// assert_eq!(parse_number("1.5万"), Some(15000.0));
+/// assert_eq!(parse_number("りんご"), None);
+/// ```
+pub fn parse_number(text: &str) -> Option<f64> {
+    let mut total = 0.0;
+    let mut section = 0.0;
+    let mut number = 0.0;
+    let mut has_number = false;
+    let mut has_section = false;
+    let mut scale: Option<f64> = None;
+    let mut is_number = false;
+
+    let chars: Vec<char> = narrow(text).chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if let Some(d) = digit(c) {
+            match scale.as_mut() {
+                Some(scale) => {
+                    *scale /= 10.0;
+                    number += d * *scale;
+                }
+                None => number = number * 10.0 + d,
+            }
+            has_number = true;
+            has_section = true;
+        } else if let Some(unit) = small_unit(c) {
+            section += if has_number { number } else { 1.0 } * unit;
+            (number, has_number, scale) = (0.0, false, None);
+            has_section = true;
+        } else if let Some(unit) = large_unit(c) {
+            if !has_section {
+                return None;
+            }
+            total += (section + number) * unit;
+            (section, number, has_number, scale) = (0.0, 0.0, false, None);
+            has_section = false;
+        } else if c == '.' && has_number && scale.is_none() {
+            scale = Some(1.0);
+            continue;
+        } else if c == ',' && has_number && scale.is_none() && is_thousands_group(&chars[i + 1..]) {
+            continue;
+        } else {
+            return None;
+        }
+        is_number = true;
+    }
+
+    is_number.then_some(total + section + number)
+}
+
+/// An extractor of the quantities mentioned in a text.
+///
+/// The text is analyzed with the given [`LinderaTokenizer`], and each run of adjacent
+/// tokens made of numerals, such as "1" "," "980" or "三" "千", immediately followed by a
+/// token whose surface is one of the units is extracted as a quantity. Tokens separated by
+/// whitespace are not merged, so "3 5円" is "5円". Numbers without a
+/// recognized unit are ignored, so dates and counts of other things do not pollute the
+/// field.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::collections::HashSet;
+///
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::quantity::QuantityExtractor;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+/// use tantivy::TantivyDocument;
+/// use tantivy::schema::{FAST, INDEXED, Schema, TEXT};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut schema_builder = Schema::builder();
+/// let body = schema_builder.add_text_field("body", TEXT);
+/// let price = schema_builder.add_f64_field("price", INDEXED | FAST);
+///
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// let mut extractor = QuantityExtractor::new(tokenizer);
+/// extractor.set_units(HashSet::from(["円".to_string()]));
+///
+/// let text = "セール価格は1,980円です";
+/// let mut document = TantivyDocument::default();
+/// document.add_text(body, text);
+/// extractor.add_to_document(&mut document, price, text)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct QuantityExtractor {
+    tokenizer: LinderaTokenizer,
+    units: HashSet<String>,
+}
+
+impl QuantityExtractor {
+    /// Creates a new `QuantityExtractor` recognizing common units of currency, counters,
+    /// percentages, lengths and weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer to analyze the text with
+    pub fn new(tokenizer: LinderaTokenizer) -> QuantityExtractor {
+        QuantityExtractor {
+            tokenizer,
+            units: DEFAULT_UNITS.iter().map(|unit| unit.to_string()).collect(),
+        }
+    }
+
+    /// Sets the units to recognize.
+    ///
+    /// # Arguments
+    ///
+    /// * `units` - The units, e.g. "円" to extract prices only
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_units(&mut self, units: HashSet<String>) -> &mut Self {
+        self.units = units.iter().map(|unit| narrow(unit)).collect();
+
+        self
+    }
+
+    /// Extracts the quantities mentioned in a text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to extract the quantities of
+    ///
+    /// # Returns
+    ///
+    /// Returns the quantities in the order of the text, or an error if the analysis fails.
+    pub fn extract(&self, text: &str) -> Result<Vec<Quantity>> {
+        let mut quantities = Vec::new();
+        let mut number = String::new();
+        let mut number_start = 0;
+        let mut number_end = 0;
+
+        for token in self.tokenizer.analyze(text)? {
+            let surface = narrow(&token.surface);
+            if surface.chars().all(is_number_char) {
+                if number.is_empty() || token.byte_start != number_end {
+                    number.clear();
+                    number_start = token.byte_start;
+                }
+                number.push_str(&surface);
+                number_end = token.byte_end;
+                continue;
+            }
+
+            if self.units.contains(&surface)
+                && let Some(value) = parse_number(&number)
+            {
+                quantities.push(Quantity {
+                    value,
+                    unit: surface,
+                    byte_start: number_start,
+                    byte_end: token.byte_end,
+                });
+            }
+            number.clear();
+        }
+
+        Ok(quantities)
+    }
+
+    /// Adds the values of the quantities mentioned in a text to a numeric field of a
+    /// document.
+    ///
+    /// The field must be an `f64` field, and should be a fast field to be filtered by range
+    /// efficiently. A document mentioning several quantities gets several values.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The document to add the values to
+    /// * `field` - The `f64` field to add the values to
+    /// * `text` - The text to extract the quantities of
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of values added, or an error if the analysis fails.
+    pub fn add_to_document(
+        &self,
+        document: &mut TantivyDocument,
+        field: Field,
+        text: &str,
+    ) -> Result<usize> {
+        let quantities = self.extract(text)?;
+        for quantity in &quantities {
+            document.add_f64(field, quantity.value);
+        }
+
+        Ok(quantities.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_number;
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("1980"), Some(1980.0));
+        assert_eq!(parse_number("1,980"), Some(1980.0));
+        assert_eq!(parse_number("１，９８０"), Some(1980.0));
+        assert_eq!(parse_number("3.5"), Some(3.5));
+        assert_eq!(parse_number("二〇二三"), Some(2023.0));
+        assert_eq!(parse_number("十五"), Some(15.0));
+        assert_eq!(parse_number("三千五百"), Some(3500.0));
+        assert_eq!(parse_number("1万2千"), Some(12000.0));
+        assert_eq!(parse_number("2億5000万"), Some(250_000_000.0));
+        assert_eq!(parse_number("100万"), Some(1_000_000.0));
+        assert_eq!(parse_number("12,345,678"), Some(12_345_678.0));
+        assert_eq!(parse_number("1,980万"), Some(19_800_000.0));
+        assert_eq!(parse_number("1,2"), None);
+        assert_eq!(parse_number("1,2345"), None);
+        assert_eq!(parse_number("1,"), None);
+        assert_eq!(parse_number(",5"), None);
+        assert_eq!(parse_number("1.2.3"), None);
+        assert_eq!(parse_number("千万"), Some(10_000_000.0));
+        assert_eq!(parse_number("万"), None);
+        assert_eq!(parse_number("億万"), None);
+        assert_eq!(parse_number("1億万"), None);
+        assert_eq!(parse_number(""), None);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_quantity_range_search() {
+        use std::collections::HashSet;
+        use std::ops::Bound;
+
+        use tantivy::collector::Count;
+        use tantivy::query::RangeQuery;
+        use tantivy::schema::{FAST, INDEXED, Schema, TEXT};
+        use tantivy::{TantivyDocument, Term};
+
+        use crate::test_helpers::{create_index, ipadic_tokenizer};
+
+        use super::QuantityExtractor;
+
+        let mut extractor = QuantityExtractor::new(ipadic_tokenizer());
+
+        let quantities = extractor.extract("りんご3個で300円").unwrap();
+        assert_eq!(quantities.len(), 2);
+        assert_eq!(quantities[0].value, 3.0);
+        assert_eq!(quantities[0].unit, "個");
+        assert_eq!(quantities[1].value, 300.0);
+        assert_eq!(quantities[1].unit, "円");
+
+        // Separators only join adjacent digits.
+        let values = |text: &str| -> Vec<f64> {
+            extractor
+                .extract(text)
+                .unwrap()
+                .iter()
+                .map(|quantity| quantity.value)
+                .collect()
+        };
+        assert_eq!(values("3 5円"), vec![5.0]);
+        assert!(values("1,2円").is_empty());
+
+        extractor.set_units(HashSet::from(["円".to_string()]));
+
+        let mut schema_builder = Schema::builder();
+        let body = schema_builder.add_text_field("body", TEXT);
+        let price = schema_builder.add_f64_field("price", INDEXED | FAST);
+        let documents = ["りんご3個で300円", "特価1,980円", "五千円の商品券"].map(|text| {
+            let mut document = TantivyDocument::default();
+            document.add_text(body, text);
+            extractor
+                .add_to_document(&mut document, price, text)
+                .unwrap();
+            document
+        });
+        let index = create_index(schema_builder.build(), documents);
+        let searcher = index.reader().unwrap().searcher();
+
+        let range = |lower: f64, upper: f64| {
+            let query = RangeQuery::new(
+                Bound::Included(Term::from_field_f64(price, lower)),
+                Bound::Included(Term::from_field_f64(price, upper)),
+            );
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(range(1000.0, 2000.0), 1);
+        assert_eq!(range(0.0, 10000.0), 3);
+        assert_eq!(range(1.0, 10.0), 0);
+    }
+}
@@ -0,0 +1,525 @@
+//! Query construction from Lindera analysis.
+//!
+//! This module provides the [`QueryBuilder`], which analyzes user input with a
+//! [`LinderaTokenizer`] and builds a Tantivy query from the resulting tokens. Unlike
+//! Tantivy's `QueryParser`, which turns the tokens of a query string without whitespace
+//! into a phrase, the builder lets the caller choose how the terms are combined, and uses
+//! the part-of-speech tags of the tokens to drop or boost terms.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use tantivy::Result;
+use tantivy::Score;
+use tantivy::TantivyError;
+use tantivy::Term;
+use tantivy::query::{BooleanQuery, BoostQuery, Occur, PhraseQuery, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+
+use crate::pos::{matches_tag, pos_tag};
+use crate::tokenizer::LinderaTokenizer;
+
+/// How the terms of an analyzed query are combined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum QueryMode {
+    /// Matches documents containing all the terms.
+    And,
+    /// Matches documents containing any of the terms.
+    Or,
+    /// Matches documents containing the terms in sequence, allowing up to `slop` moves of
+    /// the terms. Stacked tokens are alternatives at their position. The terms are not
+    /// boosted.
+    Phrase { slop: u32 },
+}
+
+/// A map from part-of-speech tags to the boosts of the terms of matching tokens.
+///
+/// A tag matches its subcategories, and the most specific matching tag wins, so
+/// "名詞,固有名詞" can be boosted more than "名詞". Terms of tokens matching no tag get a
+/// boost of 1.0. Since the tagsets differ between dictionaries, presets are provided for
+/// IPADIC, UniDic and ko-dic.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use lindera_tantivy::query::PosBoosts;
+///
+/// let pos_boosts = PosBoosts::new(HashMap::from([
+///     ("名詞".to_string(), 2.0),
+///     ("名詞,固有名詞".to_string(), 3.0),
+/// ]));
+/// assert_eq!(pos_boosts.boost("名詞,固有名詞,地域,一般"), 3.0);
+/// assert_eq!(pos_boosts.boost("名詞,一般,*,*"), 2.0);
+/// assert_eq!(pos_boosts.boost("動詞,自立,*,*"), 1.0);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PosBoosts {
+    boosts: HashMap<String, Score>,
+}
+
+impl PosBoosts {
+    /// Creates a new `PosBoosts`.
+    ///
+    /// # Arguments
+    ///
+    /// * `boosts` - The boost of each part-of-speech tag
+    pub fn new(boosts: HashMap<String, Score>) -> PosBoosts {
+        PosBoosts { boosts }
+    }
+
+    /// Returns the preset for IPADIC (and IPADIC NEologd), which favors proper nouns and
+    /// nouns over verbs and adjectives, and those over adverbs.
+    pub fn ipadic() -> PosBoosts {
+        PosBoosts::from_pairs(&[
+            ("名詞,固有名詞", 3.0),
+            ("名詞", 2.0),
+            ("動詞", 1.0),
+            ("形容詞", 1.0),
+            ("副詞", 0.5),
+        ])
+    }
+
+    /// Returns the preset for UniDic, which favors proper nouns and nouns over verbs and
+    /// adjectives, and those over pronouns and adverbs.
+    pub fn unidic() -> PosBoosts {
+        PosBoosts::from_pairs(&[
+            ("名詞,固有名詞", 3.0),
+            ("名詞", 2.0),
+            ("動詞", 1.0),
+            ("形容詞", 1.0),
+            ("代名詞", 0.5),
+            ("副詞", 0.5),
+        ])
+    }
+
+    /// Returns the preset for ko-dic, which favors proper nouns (`NNP`) and general nouns
+    /// (`NNG`) over verbs (`VV`) and adjectives (`VA`), and those over adverbs (`MAG`).
+    pub fn ko_dic() -> PosBoosts {
+        PosBoosts::from_pairs(&[
+            ("NNP", 3.0),
+            ("NNG", 2.0),
+            ("VV", 1.0),
+            ("VA", 1.0),
+            ("MAG", 0.5),
+        ])
+    }
+
+    fn from_pairs(pairs: &[(&str, Score)]) -> PosBoosts {
+        PosBoosts::new(
+            pairs
+                .iter()
+                .map(|(tag, boost)| (tag.to_string(), *boost))
+                .collect(),
+        )
+    }
+
+    /// Returns the boost of a part-of-speech tag.
+    ///
+    /// # Arguments
+    ///
+    /// * `pos_tag` - The part-of-speech tag of a token, its details separated by commas
+    ///
+    /// # Returns
+    ///
+    /// The boost of the most specific matching tag, or 1.0 if no tag matches.
+    pub fn boost(&self, pos_tag: &str) -> Score {
+        self.boosts
+            .iter()
+            .filter(|(tag, _)| matches_tag(pos_tag, tag))
+            .max_by_key(|(tag, _)| tag.len())
+            .map_or(1.0, |(_, boost)| *boost)
+    }
+}
+
+/// A builder of Tantivy queries from user input analyzed with Lindera.
+///
+/// The input is analyzed with the given [`LinderaTokenizer`], tokens whose part-of-speech
+/// tag matches one of the stop tags are dropped, and the remaining tokens are turned into
+/// terms of the given fields. Tags match their subcategories, so "助詞" drops every
+/// particle. In [`QueryMode::And`] and [`QueryMode::Or`], each term is wrapped in a
+/// `BoostQuery` according to the [`PosBoosts`] of the part-of-speech of its token; phrase
+/// queries are not boosted.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::collections::HashSet;
+///
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::query::{PosBoosts, QueryBuilder, QueryMode};
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # use tantivy::schema::{Schema, TEXT};
+/// # let mut schema_builder = Schema::builder();
+/// # let title = schema_builder.add_text_field("title", TEXT);
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// let mut query_builder = QueryBuilder::new(tokenizer, vec![title]);
+/// query_builder
+///     .set_mode(QueryMode::And)
+///     .set_stop_tags(HashSet::from(["助詞".to_string(), "助動詞".to_string()]))
+///     .set_pos_boosts(PosBoosts::ipadic());
+///
+/// // Documents containing both "東京" and "行く"
+/// let query = query_builder.build("東京に行く")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct QueryBuilder {
+    tokenizer: LinderaTokenizer,
+    fields: Vec<Field>,
+    mode: QueryMode,
+    stop_tags: HashSet<String>,
+    pos_boosts: PosBoosts,
+}
+
+impl QueryBuilder {
+    /// Creates a new `QueryBuilder` that combines the terms with [`QueryMode::Or`].
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer to analyze the input with, which should match the
+    ///   tokenizer the fields are indexed with
+    /// * `fields` - The fields to search
+    pub fn new(tokenizer: LinderaTokenizer, fields: Vec<Field>) -> QueryBuilder {
+        QueryBuilder {
+            tokenizer,
+            fields,
+            mode: QueryMode::Or,
+            stop_tags: HashSet::new(),
+            pos_boosts: PosBoosts::default(),
+        }
+    }
+
+    /// Sets how the terms are combined.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The query mode
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_mode(&mut self, mode: QueryMode) -> &mut Self {
+        self.mode = mode;
+
+        self
+    }
+
+    /// Sets the part-of-speech tags of the tokens to drop from the query.
+    ///
+    /// # Arguments
+    ///
+    /// * `stop_tags` - The tags to drop, e.g. "助詞" to drop particles
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_stop_tags(&mut self, stop_tags: HashSet<String>) -> &mut Self {
+        self.stop_tags = stop_tags;
+
+        self
+    }
+
+    /// Sets the boosts of the terms by the part-of-speech tags of their tokens.
+    ///
+    /// The boosts apply in [`QueryMode::And`] and [`QueryMode::Or`]. In
+    /// [`QueryMode::Phrase`], the terms make up a single phrase and are not boosted.
+    ///
+    /// # Arguments
+    ///
+    /// * `pos_boosts` - The boosts, e.g. [`PosBoosts::ipadic`] for an IPADIC tokenizer
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_pos_boosts(&mut self, pos_boosts: PosBoosts) -> &mut Self {
+        self.pos_boosts = pos_boosts;
+
+        self
+    }
+
+    /// Builds the query for the given input.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The user input
+    ///
+    /// # Returns
+    ///
+    /// Returns the query, which matches nothing if no term is left after analysis, or an
+    /// error if the analysis fails or, in [`QueryMode::Phrase`], if the stacked tokens make
+    /// too many alternative phrases.
+    pub fn build(&self, text: &str) -> Result<Box<dyn Query>> {
+        let mut terms: Vec<(usize, String, Score)> = Vec::new();
+        for mut token in self.tokenizer.analyze(text)? {
+            let pos_tag = pos_tag(&mut token);
+            if self.stop_tags.iter().any(|tag| matches_tag(&pos_tag, tag)) {
+                continue;
+            }
+            let boost = self.pos_boosts.boost(&pos_tag);
+            terms.push((token.position, token.surface.to_string(), boost));
+        }
+
+        let query: Box<dyn Query> = match self.mode {
+            QueryMode::And => {
+                // Tokens stacked on the same position are alternatives of each other.
+                let mut positions: BTreeMap<usize, Vec<Box<dyn Query>>> = BTreeMap::new();
+                for (position, text, boost) in &terms {
+                    positions
+                        .entry(*position)
+                        .or_default()
+                        .push(self.term_query(text, *boost));
+                }
+                Box::new(BooleanQuery::new(
+                    positions
+                        .into_values()
+                        .map(|alternatives| {
+                            let query: Box<dyn Query> = Box::new(BooleanQuery::union(alternatives));
+                            (Occur::Must, query)
+                        })
+                        .collect(),
+                ))
+            }
+            QueryMode::Or => Box::new(BooleanQuery::new(
+                terms
+                    .iter()
+                    .map(|(_, text, boost)| (Occur::Should, self.term_query(text, *boost)))
+                    .collect(),
+            )),
+            QueryMode::Phrase { slop } => Box::new(BooleanQuery::new(
+                self.fields
+                    .iter()
+                    .map(|field| Ok((Occur::Should, phrase_query(*field, &terms, slop)?)))
+                    .collect::<Result<_>>()?,
+            )),
+        };
+
+        Ok(query)
+    }
+
+    /// Builds the query of a single term searched in all the fields.
+    fn term_query(&self, text: &str, boost: Score) -> Box<dyn Query> {
+        let query: Box<dyn Query> = Box::new(BooleanQuery::new(
+            self.fields
+                .iter()
+                .map(|field| {
+                    let term = Term::from_field_text(*field, text);
+                    let query: Box<dyn Query> =
+                        Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs));
+                    (Occur::Should, query)
+                })
+                .collect(),
+        ));
+
+        if boost == 1.0 {
+            query
+        } else {
+            Box::new(BoostQuery::new(query, boost))
+        }
+    }
+}
+
+/// The maximum number of phrases a phrase query with stacked tokens expands to.
+const MAX_PHRASE_ALTERNATIVES: usize = 64;
+
+/// Builds the phrase query of the terms in a field, keeping the gaps between positions.
+///
+/// Tokens stacked on the same position are alternatives of each other, so the query matches
+/// any of the phrases made of one term per position.
+fn phrase_query(
+    field: Field,
+    terms: &[(usize, String, Score)],
+    slop: u32,
+) -> Result<Box<dyn Query>> {
+    let mut positions: BTreeMap<usize, Vec<Term>> = BTreeMap::new();
+    for (position, text, _) in terms {
+        let alternatives = positions.entry(*position).or_default();
+        let term = Term::from_field_text(field, text);
+        if !alternatives.contains(&term) {
+            alternatives.push(term);
+        }
+    }
+    let first_position = positions.keys().next().copied().unwrap_or_default();
+
+    let alternatives = positions
+        .values()
+        .try_fold(1usize, |count, terms| count.checked_mul(terms.len()));
+    if alternatives.is_none_or(|count| count > MAX_PHRASE_ALTERNATIVES) {
+        return Err(TantivyError::InvalidArgument(format!(
+            "the phrase has more than {MAX_PHRASE_ALTERNATIVES} alternatives"
+        )));
+    }
+
+    let mut phrases: Vec<Vec<(usize, Term)>> = vec![Vec::new()];
+    for (position, alternatives) in &positions {
+        phrases = phrases
+            .iter()
+            .flat_map(|phrase| {
+                alternatives.iter().map(move |term| {
+                    let mut phrase = phrase.clone();
+                    phrase.push((position - first_position, term.clone()));
+                    phrase
+                })
+            })
+            .collect();
+    }
+
+    let mut queries: Vec<Box<dyn Query>> = phrases
+        .into_iter()
+        .filter(|phrase| !phrase.is_empty())
+        .map(|mut phrase| -> Box<dyn Query> {
+            if phrase.len() == 1 {
+                let (_, term) = phrase.remove(0);
+                Box::new(TermQuery::new(term, IndexRecordOption::WithFreqs))
+            } else {
+                Box::new(PhraseQuery::new_with_offset_and_slop(phrase, slop))
+            }
+        })
+        .collect();
+
+    Ok(match queries.len() {
+        1 => queries.remove(0),
+        _ => Box::new(BooleanQuery::union(queries)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PosBoosts;
+
+    #[test]
+    fn test_pos_boosts_presets() {
+        let ipadic = PosBoosts::ipadic();
+        assert_eq!(ipadic.boost("名詞,固有名詞,地域,一般"), 3.0);
+        assert_eq!(ipadic.boost("名詞,一般,*,*"), 2.0);
+        assert_eq!(ipadic.boost("副詞,一般,*,*"), 0.5);
+        assert_eq!(ipadic.boost("助詞,格助詞,一般,*"), 1.0);
+
+        let unidic = PosBoosts::unidic();
+        assert_eq!(unidic.boost("名詞,普通名詞,一般,*"), 2.0);
+        assert_eq!(unidic.boost("代名詞,*,*,*"), 0.5);
+
+        let ko_dic = PosBoosts::ko_dic();
+        assert_eq!(ko_dic.boost("NNP,*,F,하네다"), 3.0);
+        assert_eq!(ko_dic.boost("JKS,*,F,가"), 1.0);
+    }
+
+    #[cfg(feature = "embed-ipadic")]
+    fn search(
+        mode: super::QueryMode,
+        stop_tags: &[&str],
+        boost: tantivy::Score,
+        text: &str,
+    ) -> Vec<u32> {
+        use std::collections::HashMap;
+
+        use tantivy::collector::TopDocs;
+        use tantivy::doc;
+        use tantivy::schema::Schema;
+
+        use crate::test_helpers::{create_index, ipadic_tokenizer, text_options};
+
+        use super::{PosBoosts, QueryBuilder};
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", text_options());
+        let index = create_index(
+            schema_builder.build(),
+            ["東京に行く", "東京の空港", "大阪に行く", "東京の羽田"]
+                .map(|text| doc!(title => text)),
+        );
+
+        let mut query_builder = QueryBuilder::new(ipadic_tokenizer(), vec![title]);
+        query_builder
+            .set_mode(mode)
+            .set_stop_tags(stop_tags.iter().map(|tag| tag.to_string()).collect())
+            .set_pos_boosts(PosBoosts::new(HashMap::from([("名詞".to_string(), boost)])));
+        let query = query_builder.build(text).unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        searcher
+            .search(&query, &TopDocs::with_limit(10))
+            .unwrap()
+            .into_iter()
+            .map(|(_, address)| address.doc_id)
+            .collect()
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_query_builder_and() {
+        use super::QueryMode;
+
+        assert_eq!(search(QueryMode::And, &[], 1.0, "東京に行く"), vec![0]);
+        assert!(search(QueryMode::And, &[], 1.0, "東京は行く").is_empty());
+        assert_eq!(
+            search(QueryMode::And, &["助詞"], 1.0, "東京は行く"),
+            vec![0]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_query_builder_or_with_boost() {
+        use super::QueryMode;
+
+        // "行く" is rarer than "東京", so "大阪に行く" follows the document with both terms.
+        let unboosted = search(QueryMode::Or, &["助詞"], 1.0, "東京に行く");
+        assert_eq!(unboosted.len(), 4);
+        assert_eq!(unboosted[..2], [0, 2]);
+
+        // Boosting the nouns ranks the documents with "東京" above it.
+        let boosted = search(QueryMode::Or, &["助詞"], 10.0, "東京に行く");
+        assert_eq!(boosted.len(), 4);
+        assert_eq!((boosted[0], boosted[3]), (0, 2));
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_query_builder_phrase() {
+        use super::QueryMode;
+
+        let phrase = QueryMode::Phrase { slop: 0 };
+        assert_eq!(search(phrase, &[], 1.0, "東京に行く"), vec![0]);
+        assert!(search(phrase, &[], 1.0, "行く東京").is_empty());
+        assert_eq!(search(phrase, &["助詞"], 1.0, "東京は行く"), vec![0]);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_query_builder_phrase_with_stacked_tokens() {
+        use tantivy::collector::Count;
+        use tantivy::doc;
+        use tantivy::schema::Schema;
+
+        use crate::test_helpers::{create_index, ipadic_tokenizer, text_options};
+        use crate::token_filter::japanese_romaji::JapaneseRomajiTokenFilter;
+
+        use super::{QueryBuilder, QueryMode};
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", text_options());
+        let index = create_index(
+            schema_builder.build(),
+            ["東京に行く", "tokyo ni iku", "行く東京に"].map(|text| doc!(title => text)),
+        );
+
+        // The romaji is stacked on each token of the query only.
+        let mut query_tokenizer = ipadic_tokenizer();
+        query_tokenizer.append_token_filter(JapaneseRomajiTokenFilter::new(false).into());
+        let mut query_builder = QueryBuilder::new(query_tokenizer, vec![title]);
+        query_builder.set_mode(QueryMode::Phrase { slop: 0 });
+        let query = query_builder.build("東京に行く").unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 2);
+    }
+}
@@ -0,0 +1,383 @@
+//! Romaji transliteration for Japanese.
+//!
+//! This module converts katakana readings to romaji and normalizes romaji spellings, so
+//! that "tokyo", "toukyou" and "tōkyō" are all indexed and searched as the same term.
+//! It also provides the [`RomajiTokenizer`], the query-side counterpart of
+//! [`JapaneseRomajiTokenFilter`](crate::token_filter::japanese_romaji::JapaneseRomajiTokenFilter).
+
+use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+/// The romanization system used to transliterate katakana.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RomajiSystem {
+    /// Hepburn romanization (e.g. "shi", "chi", "tsu", "fu", "ji").
+    Hepburn,
+    /// Kunrei-shiki romanization (e.g. "si", "ti", "tu", "hu", "zi").
+    Kunrei,
+}
+
+/// Returns the romaji of a single katakana character, or `None` if it is not a katakana
+/// syllable.
+fn syllable(c: char, system: RomajiSystem) -> Option<&'static str> {
+    let kunrei = system == RomajiSystem::Kunrei;
+    let romaji = match c {
+        'ア' => "a",
+        'イ' => "i",
+        'ウ' => "u",
+        'エ' => "e",
+        'オ' => "o",
+        'カ' => "ka",
+        'キ' => "ki",
+        'ク' => "ku",
+        'ケ' => "ke",
+        'コ' => "ko",
+        'サ' => "sa",
+        'シ' if kunrei => "si",
+        'シ' => "shi",
+        'ス' => "su",
+        'セ' => "se",
+        'ソ' => "so",
+        'タ' => "ta",
+        'チ' if kunrei => "ti",
+        'チ' => "chi",
+        'ツ' if kunrei => "tu",
+        'ツ' => "tsu",
+        'テ' => "te",
+        'ト' => "to",
+        'ナ' => "na",
+        'ニ' => "ni",
+        'ヌ' => "nu",
+        'ネ' => "ne",
+        'ノ' => "no",
+        'ハ' => "ha",
+        'ヒ' => "hi",
+        'フ' if kunrei => "hu",
+        'フ' => "fu",
+        'ヘ' => "he",
+        'ホ' => "ho",
+        'マ' => "ma",
+        'ミ' => "mi",
+        'ム' => "mu",
+        'メ' => "me",
+        'モ' => "mo",
+        'ヤ' => "ya",
+        'ユ' => "yu",
+        'ヨ' => "yo",
+        'ラ' => "ra",
+        'リ' => "ri",
+        'ル' => "ru",
+        'レ' => "re",
+        'ロ' => "ro",
+        'ワ' => "wa",
+        'ヰ' => "i",
+        'ヱ' => "e",
+        'ヲ' => "o",
+        'ン' => "n",
+        'ガ' => "ga",
+        'ギ' => "gi",
+        'グ' => "gu",
+        'ゲ' => "ge",
+        'ゴ' => "go",
+        'ザ' => "za",
+        'ジ' | 'ヂ' if kunrei => "zi",
+        'ジ' | 'ヂ' => "ji",
+        'ズ' | 'ヅ' => "zu",
+        'ゼ' => "ze",
+        'ゾ' => "zo",
+        'ダ' => "da",
+        'デ' => "de",
+        'ド' => "do",
+        'バ' => "ba",
+        'ビ' => "bi",
+        'ブ' => "bu",
+        'ベ' => "be",
+        'ボ' => "bo",
+        'パ' => "pa",
+        'ピ' => "pi",
+        'プ' => "pu",
+        'ペ' => "pe",
+        'ポ' => "po",
+        'ヴ' => "vu",
+        'ァ' => "a",
+        'ィ' => "i",
+        'ゥ' => "u",
+        'ェ' => "e",
+        'ォ' => "o",
+        'ャ' => "ya",
+        'ュ' => "yu",
+        'ョ' => "yo",
+        'ヮ' => "wa",
+        _ => return None,
+    };
+    Some(romaji)
+}
+
+fn is_small(c: char) -> bool {
+    matches!(
+        c,
+        'ァ' | 'ィ' | 'ゥ' | 'ェ' | 'ォ' | 'ャ' | 'ュ' | 'ョ' | 'ヮ'
+    )
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+/// Transliterates a katakana text to romaji.
+///
+/// Contracted sounds (e.g. "キャ", "シェ", "ファ") are combined, the geminate consonant
+/// mark "ッ" doubles the following consonant, and the prolonged sound mark "ー" repeats
+/// the preceding vowel. Returns `None` if the text contains anything other than katakana.
+///
+/// # Examples
+///
+/// ```
+/// use lindera_tantivy::romaji::{RomajiSystem, to_romaji};
+///
+/// assert_eq!(to_romaji("トウキョウ", RomajiSystem::Hepburn).unwrap(), "toukyou");
+/// assert_eq!(to_romaji("シンブン", RomajiSystem::Kunrei).unwrap(), "sinbun");
+/// assert_eq!(to_romaji("マッチャ", RomajiSystem::Hepburn).unwrap(), "matcha");
+/// ```
+pub fn to_romaji(katakana: &str, system: RomajiSystem) -> Option<String> {
+    let mut romaji = String::with_capacity(katakana.len());
+    let mut geminate = false;
+    let mut previous: Option<char> = None;
+
+    for c in katakana.chars() {
+        match c {
+            'ッ' => {
+                geminate = true;
+                previous = None;
+                continue;
+            }
+            'ー' => {
+                if let Some(vowel) = romaji.chars().last().filter(|c| is_vowel(*c)) {
+                    romaji.push(vowel);
+                }
+                previous = None;
+                continue;
+            }
+            _ => {}
+        }
+
+        let syllable = syllable(c, system)?;
+        let mut syllable = syllable.to_string();
+
+        if matches!(c, 'ャ' | 'ュ' | 'ョ') && romaji.ends_with('i') && romaji.len() > 1 {
+            // Contracted sounds with a small "ya", "yu" or "yo" (e.g. "kya", "sha", "ja").
+            romaji.pop();
+            if romaji.ends_with("sh") || romaji.ends_with("ch") || romaji.ends_with('j') {
+                syllable.remove(0);
+            }
+        } else if matches!(c, 'ァ' | 'ィ' | 'ゥ' | 'ェ' | 'ォ')
+            && previous.is_some_and(|previous| !is_small(previous))
+        {
+            // Contracted sounds of loanwords (e.g. "fa", "ti", "she", "wi", "ye").
+            match romaji.pop() {
+                Some('u') if previous == Some('ウ') => romaji.push('w'),
+                Some('i') if previous == Some('イ') => romaji.push('y'),
+                _ => {}
+            }
+        }
+
+        if geminate {
+            geminate = false;
+            match syllable.chars().next() {
+                Some('c') => romaji.push('t'),
+                Some(consonant) if !is_vowel(consonant) && consonant != 'n' => {
+                    romaji.push(consonant)
+                }
+                _ => {}
+            }
+        }
+        romaji.push_str(&syllable);
+        previous = Some(c);
+    }
+
+    Some(romaji)
+}
+
+/// Normalizes a romaji spelling to the form used for indexing and searching.
+///
+/// The text is lowercased, vowels with macrons or circumflexes are replaced by plain
+/// vowels, apostrophes and hyphens are removed, "m" before "b" and "p" is replaced by
+/// "n", and long vowels ("aa", "ii", "uu", "ee", "oo", "ou") are collapsed to a single
+/// vowel. This makes "Tōkyō", "toukyou" and "tokyo" the same term.
+///
+/// # Examples
+///
+/// ```
+/// use lindera_tantivy::romaji::normalize_romaji;
+///
+/// assert_eq!(normalize_romaji("Tōkyō"), "tokyo");
+/// assert_eq!(normalize_romaji("toukyou"), "tokyo");
+/// assert_eq!(normalize_romaji("shimbun"), "shinbun");
+/// ```
+pub fn normalize_romaji(romaji: &str) -> String {
+    let mut normalized = String::with_capacity(romaji.len());
+
+    for c in romaji.chars().flat_map(char::to_lowercase) {
+        let c = match c {
+            'ā' | 'â' => 'a',
+            'ī' | 'î' => 'i',
+            'ū' | 'û' => 'u',
+            'ē' | 'ê' => 'e',
+            'ō' | 'ô' => 'o',
+            '\'' | '’' | '-' => continue,
+            c => c,
+        };
+
+        match (normalized.chars().last(), c) {
+            (Some(previous), c)
+                if is_vowel(c) && (previous == c || (previous, c) == ('o', 'u')) =>
+            {
+                continue;
+            }
+            (Some('m'), 'b' | 'p') => {
+                normalized.pop();
+                normalized.push('n');
+            }
+            _ => {}
+        }
+        normalized.push(c);
+    }
+
+    normalized
+}
+
+/// A Tantivy tokenizer for romaji queries.
+///
+/// `RomajiTokenizer` splits the text on characters other than alphanumerics, apostrophes
+/// and hyphens, and normalizes each word with [`normalize_romaji`]. It is used on the
+/// query side of a field indexed with
+/// [`JapaneseRomajiTokenFilter`](crate::token_filter::japanese_romaji::JapaneseRomajiTokenFilter),
+/// so that a query like "tokyo kuukou" matches documents containing "東京空港".
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use tantivy::query::QueryParser;
+/// use tantivy::tokenizer::TokenizerManager;
+/// use lindera_tantivy::romaji::RomajiTokenizer;
+///
+/// // `index` has a field `title` indexed with the "lang_ja" tokenizer, a
+/// // `LinderaTokenizer` with a `JapaneseRomajiTokenFilter`.
+/// let query_tokenizers = TokenizerManager::default();
+/// query_tokenizers.register("lang_ja", RomajiTokenizer::default());
+///
+/// let query_parser = QueryParser::new(index.schema(), vec![title], query_tokenizers);
+/// let query = query_parser.parse_query("tokyo kuukou")?;
+/// ```
+#[derive(Clone, Default)]
+pub struct RomajiTokenizer {
+    token: Token,
+}
+
+/// A token stream produced by [`RomajiTokenizer`].
+pub struct RomajiTokenStream<'a> {
+    text: &'a str,
+    offset: usize,
+    token: &'a mut Token,
+}
+
+impl Tokenizer for RomajiTokenizer {
+    type TokenStream<'a> = RomajiTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> RomajiTokenStream<'a> {
+        self.token.reset();
+        RomajiTokenStream {
+            text,
+            offset: 0,
+            token: &mut self.token,
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '\'' | '’' | '-')
+}
+
+impl TokenStream for RomajiTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        let rest = &self.text[self.offset..];
+        let Some(start) = rest.find(is_word_char) else {
+            self.offset = self.text.len();
+            return false;
+        };
+        let end = rest[start..]
+            .find(|c: char| !is_word_char(c))
+            .map_or(rest.len(), |end| start + end);
+
+        self.token.text = normalize_romaji(&rest[start..end]);
+        self.token.offset_from = self.offset + start;
+        self.token.offset_to = self.offset + end;
+        self.token.position = self.token.position.wrapping_add(1);
+        self.offset += end;
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+    use super::{RomajiSystem, RomajiTokenizer, normalize_romaji, to_romaji};
+
+    #[test]
+    fn test_to_romaji_hepburn() {
+        let hepburn = |text| to_romaji(text, RomajiSystem::Hepburn).unwrap();
+        assert_eq!(hepburn("クウコウ"), "kuukou");
+        assert_eq!(hepburn("シンジュク"), "shinjuku");
+        assert_eq!(hepburn("キョウト"), "kyouto");
+        assert_eq!(hepburn("チャ"), "cha");
+        assert_eq!(hepburn("ジョウ"), "jou");
+        assert_eq!(hepburn("ホッカイドウ"), "hokkaidou");
+        assert_eq!(hepburn("コンピューター"), "konpyuutaa");
+        assert_eq!(hepburn("ファイル"), "fairu");
+        assert_eq!(hepburn("パーティー"), "paatii");
+        assert_eq!(hepburn("ウィキ"), "wiki");
+        assert_eq!(hepburn("シェア"), "shea");
+        assert_eq!(to_romaji("東京", RomajiSystem::Hepburn), None);
+    }
+
+    #[test]
+    fn test_to_romaji_kunrei() {
+        let kunrei = |text| to_romaji(text, RomajiSystem::Kunrei).unwrap();
+        assert_eq!(kunrei("シンジュク"), "sinzyuku");
+        assert_eq!(kunrei("チャ"), "tya");
+        assert_eq!(kunrei("フジ"), "huzi");
+        assert_eq!(kunrei("ツクバ"), "tukuba");
+    }
+
+    #[test]
+    fn test_normalize_romaji() {
+        assert_eq!(normalize_romaji("kuukou"), "kuko");
+        assert_eq!(normalize_romaji("Kūkō"), "kuko");
+        assert_eq!(normalize_romaji("Tokyo"), "tokyo");
+        assert_eq!(normalize_romaji("shin'ichi"), "shinichi");
+        assert_eq!(normalize_romaji("Nihombashi"), "nihonbashi");
+    }
+
+    #[test]
+    fn test_romaji_tokenizer() {
+        let mut tokenizer = RomajiTokenizer::default();
+        let mut tokens: Vec<Token> = vec![];
+        tokenizer
+            .token_stream("Tokyo, kuukou")
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].text, "tokyo");
+        assert_eq!((tokens[0].position, tokens[0].offset_from), (0, 0));
+        assert_eq!(tokens[1].text, "kuko");
+        assert_eq!((tokens[1].position, tokens[1].offset_from), (1, 7));
+    }
+}
@@ -0,0 +1,285 @@
+//! Segmentation over a shared dictionary.
+//!
+//! Lindera's `Segmenter` owns its dictionary, so every tokenizer built on it, and every clone
+//! of such a tokenizer, holds a full copy of the dictionary. The [`SharedSegmenter`] runs the
+//! same Viterbi lattice over an `Arc<Dictionary>` instead, so that the tokenizers built from
+//! the [`dictionary`](crate::dictionary) registry and their clones share one copy.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+
+use lindera::dictionary::{Dictionary, Lattice, UserDictionary, WordId};
+use lindera::mode::Mode;
+use lindera::segmenter::Segmenter;
+use lindera::token::Token as LToken;
+
+/// A segmenter sharing its dictionary, which segments a text as Lindera's `Segmenter` does.
+#[derive(Clone)]
+pub(crate) struct SharedSegmenter {
+    mode: Mode,
+    dictionary: Arc<Dictionary>,
+    user_dictionary: Option<Arc<UserDictionary>>,
+    keep_whitespace: bool,
+}
+
+impl SharedSegmenter {
+    /// Creates a new `SharedSegmenter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The segmentation mode
+    /// * `dictionary` - The shared dictionary
+    /// * `user_dictionary` - An optional user dictionary
+    /// * `keep_whitespace` - Whether to emit the whitespace tokens, which Lindera skips by
+    ///   default for MeCab compatibility
+    pub(crate) fn new(
+        mode: Mode,
+        dictionary: Arc<Dictionary>,
+        user_dictionary: Option<UserDictionary>,
+        keep_whitespace: bool,
+    ) -> SharedSegmenter {
+        SharedSegmenter {
+            mode,
+            dictionary,
+            user_dictionary: user_dictionary.map(Arc::new),
+            keep_whitespace,
+        }
+    }
+
+    /// Creates a new `SharedSegmenter` taking over the dictionary of a Lindera `Segmenter`.
+    ///
+    /// The dictionary is moved, not copied, and is shared by the clones of the segmenter.
+    pub(crate) fn from_segmenter(segmenter: Segmenter) -> SharedSegmenter {
+        SharedSegmenter::new(
+            segmenter.mode,
+            Arc::new(segmenter.dictionary),
+            segmenter.user_dictionary,
+            segmenter.keep_whitespace,
+        )
+    }
+
+    /// Segments a text along the best path of the lattice of each of its sentences.
+    pub(crate) fn segment<'a>(&'a self, text: Cow<'a, str>) -> Vec<LToken<'a>> {
+        let borrowed = borrowed(&text);
+        let mut lattice = Lattice::default();
+        let mut tokens = Vec::new();
+        for (sentence_start, sentence_end) in sentences(&text) {
+            let sentence = &text[sentence_start..sentence_end];
+            lattice.set_text(
+                &self.dictionary.prefix_dictionary,
+                &self.user_dictionary.as_ref().map(|d| &d.dict),
+                &self.dictionary.character_definition,
+                &self.dictionary.unknown_dictionary,
+                &self.dictionary.connection_cost_matrix,
+                sentence,
+                &self.mode,
+            );
+            self.push_tokens(
+                &mut tokens,
+                &text,
+                borrowed,
+                (sentence_start, sentence_end),
+                &lattice.tokens_offset(),
+            );
+        }
+
+        tokens
+    }
+
+    /// Segments a text along the top-N paths of the lattice of each of its sentences.
+    ///
+    /// The paths of the same rank in each sentence are concatenated, and the results are
+    /// ordered by cost, best first.
+    pub(crate) fn segment_nbest<'a>(
+        &'a self,
+        text: Cow<'a, str>,
+        n: usize,
+        cost_threshold: Option<i64>,
+    ) -> Vec<(Vec<LToken<'a>>, i64)> {
+        let borrowed = borrowed(&text);
+        let mut lattice = Lattice::default();
+        let mut paths: Vec<(Vec<LToken<'a>>, i64)> = Vec::with_capacity(n);
+        for (sentence_start, sentence_end) in sentences(&text) {
+            let sentence = &text[sentence_start..sentence_end];
+            lattice.set_text_nbest(
+                &self.dictionary.prefix_dictionary,
+                &self.user_dictionary.as_ref().map(|d| &d.dict),
+                &self.dictionary.character_definition,
+                &self.dictionary.unknown_dictionary,
+                &self.dictionary.connection_cost_matrix,
+                sentence,
+                &self.mode,
+            );
+            let offsets = lattice.nbest_tokens_offset(n, true, cost_threshold);
+            for (rank, (offsets, cost)) in offsets.into_iter().enumerate() {
+                if rank >= paths.len() {
+                    paths.resize_with(rank + 1, Default::default);
+                }
+                let (tokens, path_cost) = &mut paths[rank];
+                *path_cost += cost;
+                self.push_tokens(
+                    tokens,
+                    &text,
+                    borrowed,
+                    (sentence_start, sentence_end),
+                    &offsets,
+                );
+            }
+        }
+
+        paths
+    }
+
+    /// Appends the tokens of the word offsets of a sentence, skipping the whitespace tokens
+    /// unless they are kept. The surfaces borrow the text if it is borrowed.
+    fn push_tokens<'a>(
+        &'a self,
+        tokens: &mut Vec<LToken<'a>>,
+        text: &str,
+        borrowed: Option<&'a str>,
+        (sentence_start, sentence_end): (usize, usize),
+        offsets: &[(usize, WordId)],
+    ) {
+        let space_category_id = if self.keep_whitespace {
+            None
+        } else {
+            self.dictionary
+                .character_definition
+                .category_id_by_name("SPACE")
+        };
+
+        for (i, (start, word_id)) in offsets.iter().enumerate() {
+            let byte_start = sentence_start + start;
+            let byte_end = offsets
+                .get(i + 1)
+                .map_or(sentence_end, |(next_start, _)| sentence_start + next_start);
+
+            if let Some(space_category_id) = space_category_id
+                && text[byte_start..byte_end].chars().all(|c| {
+                    self.dictionary
+                        .character_definition
+                        .lookup_categories(c)
+                        .contains(&space_category_id)
+                })
+            {
+                continue;
+            }
+
+            let surface = match borrowed {
+                Some(text) => Cow::Borrowed(&text[byte_start..byte_end]),
+                None => Cow::Owned(text[byte_start..byte_end].to_string()),
+            };
+            tokens.push(LToken::new(
+                surface,
+                byte_start,
+                byte_end,
+                tokens.len(),
+                *word_id,
+                &self.dictionary,
+                self.user_dictionary.as_deref(),
+            ));
+        }
+    }
+}
+
+/// Returns the text if it is borrowed for the lifetime of the tokens.
+fn borrowed<'a>(text: &Cow<'a, str>) -> Option<&'a str> {
+    match text {
+        Cow::Borrowed(text) => Some(text),
+        Cow::Owned(_) => None,
+    }
+}
+
+/// Returns the byte ranges of the sentences of a text, which end after a newline, a tab,
+/// "。" or "、", as in Lindera.
+fn sentences(text: &str) -> Vec<(usize, usize)> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    for (offset, c) in text.char_indices() {
+        if matches!(c, '\n' | '\t' | '。' | '、') {
+            let end = offset + c.len_utf8();
+            sentences.push((start, end));
+            start = end;
+        }
+    }
+    if start < text.len() {
+        sentences.push((start, text.len()));
+    }
+
+    sentences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sentences;
+
+    #[test]
+    fn test_sentences() {
+        assert_eq!(
+            sentences("東京。大阪、\n京都"),
+            vec![(0, 9), (9, 18), (18, 19), (19, 25)]
+        );
+        assert_eq!(sentences("東京。"), vec![(0, 9)]);
+        assert!(sentences("").is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_segment_like_lindera() {
+        use std::borrow::Cow;
+        use std::sync::Arc;
+
+        use lindera::dictionary::load_dictionary;
+        use lindera::mode::Mode;
+        use lindera::segmenter::Segmenter;
+
+        use super::SharedSegmenter;
+
+        let text = "関西国際空港 限定。トートバッグ\n東京都";
+        let key = |token: &lindera::token::Token<'_>| {
+            (
+                token.surface.to_string(),
+                token.byte_start,
+                token.byte_end,
+                token.position,
+                token.word_id.id(),
+            )
+        };
+
+        let segmenter = Segmenter::new(
+            Mode::Normal,
+            load_dictionary("embedded://ipadic").unwrap(),
+            None,
+        );
+        let expected: Vec<_> = segmenter
+            .segment(Cow::Borrowed(text))
+            .unwrap()
+            .iter()
+            .map(key)
+            .collect();
+        let expected_nbest: Vec<_> = segmenter
+            .segment_nbest(Cow::Borrowed(text), 3, true, None)
+            .unwrap()
+            .iter()
+            .map(|(tokens, cost)| (tokens.iter().map(key).collect::<Vec<_>>(), *cost))
+            .collect();
+
+        let shared = SharedSegmenter::from_segmenter(segmenter);
+        let tokens: Vec<_> = shared
+            .segment(Cow::Borrowed(text))
+            .iter()
+            .map(key)
+            .collect();
+        assert_eq!(tokens, expected);
+        let nbest: Vec<_> = shared
+            .segment_nbest(Cow::Borrowed(text), 3, None)
+            .iter()
+            .map(|(tokens, cost)| (tokens.iter().map(key).collect::<Vec<_>>(), *cost))
+            .collect();
+        assert_eq!(nbest, expected_nbest);
+
+        // The clones share the dictionary.
+        let clone = shared.clone();
+        assert!(Arc::ptr_eq(&clone.dictionary, &shared.dictionary));
+    }
+}
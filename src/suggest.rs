@@ -0,0 +1,342 @@
+//! Spelling suggestions.
+//!
+//! This module provides the [`Suggester`], which proposes corrections for words missing
+//! from the vocabulary of a field, comparing the kana readings of the words as well as
+//! their surfaces, so that "コンピュータ" is corrected to "コンピューター" and "林檎" to
+//! "りんご".
+
+use std::collections::HashMap;
+
+use tantivy::Result;
+use tantivy::Searcher;
+use tantivy::schema::Field;
+
+use lindera::token::Token as LToken;
+use lindera::token_filter::japanese_kana::KanaKind;
+
+use crate::token_filter::japanese_kana_fold::{fold_kana, is_reading};
+use crate::tokenizer::LinderaTokenizer;
+
+/// A correction proposed by [`Suggester`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The text of the term of the vocabulary.
+    pub text: String,
+    /// The edit distance between the word and the term.
+    pub distance: usize,
+    /// The number of documents containing the term.
+    pub doc_frequency: u64,
+}
+
+/// A term of the vocabulary of the suggester.
+#[derive(Clone, Debug)]
+struct Entry {
+    text: String,
+    reading: String,
+    doc_frequency: u64,
+}
+
+/// Returns the katakana reading of a token, or its surface folded to katakana if it has
+/// none, e.g. for unknown words.
+fn token_reading(token: &mut LToken<'_>) -> String {
+    match token.get("reading").filter(|reading| is_reading(reading)) {
+        Some(reading) => reading.to_string(),
+        None => fold_kana(&token.surface, &KanaKind::Katakana),
+    }
+}
+
+/// Returns the Levenshtein distance between two texts, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// A "did you mean" suggester over the vocabulary of a field.
+///
+/// The vocabulary is read from the term dictionaries of a field indexed with a
+/// [`LinderaTokenizer`], and the reading of each term is computed with the same tokenizer.
+/// The distance between a word and a term is the smaller of the edit distances between
+/// their surfaces and between their katakana readings, so words written in different
+/// scripts or with a different prolonged sound mark are close to each other. Words and
+/// terms without a reading are compared by their surface folded to katakana.
+///
+/// The suggester scans the whole vocabulary for each word, and is meant for fields with a
+/// moderate vocabulary such as titles or tags. It has to be rebuilt to see new terms.
+///
+/// # Examples
+///
+/// ```rust
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::suggest::Suggester;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # #[cfg(feature = "embed-ipadic")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # use tantivy::Index;
+/// # use tantivy::schema::{IndexRecordOption, Schema, TextFieldIndexing, TextOptions};
+/// # let mut schema_builder = Schema::builder();
+/// # let title = schema_builder.add_text_field(
+/// #     "title",
+/// #     TextOptions::default().set_indexing_options(
+/// #         TextFieldIndexing::default()
+/// #             .set_tokenizer("lang_ja")
+/// #             .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+/// #     ),
+/// # );
+/// # let index = Index::create_in_ram(schema_builder.build());
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+/// # index.tokenizers().register("lang_ja", tokenizer.clone());
+///
+/// let searcher = index.reader()?.searcher();
+/// let suggester = Suggester::new(tokenizer, &searcher, title)?;
+///
+/// if let Some(corrected) = suggester.did_you_mean("コンピュータを買う")? {
+///     println!("Did you mean {corrected}?");
+/// }
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "embed-ipadic"))]
+/// # fn main() {}
+/// ```
+#[derive(Clone)]
+pub struct Suggester {
+    tokenizer: LinderaTokenizer,
+    entries: Vec<Entry>,
+    index: HashMap<String, usize>,
+    max_distance: usize,
+}
+
+impl Suggester {
+    /// Creates a new `Suggester` from the vocabulary of a field, proposing terms up to an
+    /// edit distance of 2.
+    ///
+    /// # Arguments
+    ///
+    /// * `tokenizer` - The tokenizer the field is indexed with
+    /// * `searcher` - The searcher of the index
+    /// * `field` - The field whose terms make the vocabulary
+    ///
+    /// # Returns
+    ///
+    /// Returns the suggester, or an error if the term dictionaries cannot be read or a term
+    /// cannot be analyzed.
+    pub fn new(
+        tokenizer: LinderaTokenizer,
+        searcher: &Searcher,
+        field: Field,
+    ) -> Result<Suggester> {
+        let mut doc_frequencies: HashMap<String, u64> = HashMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(field)?;
+            let mut stream = inverted_index.terms().stream()?;
+            while let Some((key, term_info)) = stream.next() {
+                if let Ok(text) = std::str::from_utf8(key) {
+                    *doc_frequencies.entry(text.to_string()).or_default() +=
+                        u64::from(term_info.doc_freq);
+                }
+            }
+        }
+
+        let mut entries = Vec::with_capacity(doc_frequencies.len());
+        for (text, doc_frequency) in doc_frequencies {
+            let reading = tokenizer
+                .analyze(&text)?
+                .iter_mut()
+                .map(token_reading)
+                .collect::<String>();
+            entries.push(Entry {
+                text,
+                reading,
+                doc_frequency,
+            });
+        }
+        entries.sort_by(|a, b| a.text.cmp(&b.text));
+
+        let index = entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| (entry.text.clone(), i))
+            .collect();
+
+        Ok(Suggester {
+            tokenizer,
+            entries,
+            index,
+            max_distance: 2,
+        })
+    }
+
+    /// Sets the maximum edit distance of the proposed terms.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_distance` - The maximum edit distance
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    pub fn set_max_distance(&mut self, max_distance: usize) -> &mut Self {
+        self.max_distance = max_distance;
+
+        self
+    }
+
+    /// Returns whether a term is in the vocabulary.
+    pub fn contains(&self, text: &str) -> bool {
+        self.index.contains_key(text)
+    }
+
+    /// Proposes terms of the vocabulary close to a word.
+    fn suggest_reading(&self, text: &str, reading: &str, limit: usize) -> Vec<Suggestion> {
+        // A word cannot be corrected into anything by deleting all its characters.
+        let max_distance = self
+            .max_distance
+            .min(text.chars().count().saturating_sub(1));
+
+        let mut suggestions: Vec<Suggestion> = self
+            .entries
+            .iter()
+            .filter(|entry| entry.text != text)
+            .filter_map(|entry| {
+                let distance =
+                    edit_distance(text, &entry.text).min(edit_distance(reading, &entry.reading));
+                (distance <= max_distance).then(|| Suggestion {
+                    text: entry.text.clone(),
+                    distance,
+                    doc_frequency: entry.doc_frequency,
+                })
+            })
+            .collect();
+
+        suggestions.sort_by(|a, b| {
+            a.distance
+                .cmp(&b.distance)
+                .then_with(|| b.doc_frequency.cmp(&a.doc_frequency))
+                .then_with(|| a.text.cmp(&b.text))
+        });
+        suggestions.truncate(limit);
+
+        suggestions
+    }
+
+    /// Proposes terms of the vocabulary close to a word.
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - A single word, as it would be indexed
+    /// * `limit` - The maximum number of suggestions
+    ///
+    /// # Returns
+    ///
+    /// Returns the suggestions ordered by increasing distance and decreasing document
+    /// frequency, or an error if the word cannot be analyzed.
+    pub fn suggest(&self, word: &str, limit: usize) -> Result<Vec<Suggestion>> {
+        let reading = self
+            .tokenizer
+            .analyze(word)?
+            .iter_mut()
+            .map(token_reading)
+            .collect::<String>();
+
+        Ok(self.suggest_reading(word, &reading, limit))
+    }
+
+    /// Corrects the words of a query missing from the vocabulary.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The query text
+    ///
+    /// # Returns
+    ///
+    /// Returns the text with each word missing from the vocabulary replaced with the best
+    /// suggestion, or `None` if there is nothing to correct, or an error if the text cannot
+    /// be analyzed.
+    pub fn did_you_mean(&self, text: &str) -> Result<Option<String>> {
+        let mut corrected = String::with_capacity(text.len());
+        let mut offset = 0;
+        let mut changed = false;
+
+        for mut token in self.tokenizer.analyze(text)? {
+            if self.contains(&token.surface) || token.byte_start < offset {
+                continue;
+            }
+            let reading = token_reading(&mut token);
+            let Some(suggestion) = self.suggest_reading(&token.surface, &reading, 1).pop() else {
+                continue;
+            };
+
+            corrected.push_str(&text[offset..token.byte_start]);
+            corrected.push_str(&suggestion.text);
+            offset = token.byte_end;
+            changed = true;
+        }
+        corrected.push_str(&text[offset..]);
+
+        Ok(changed.then_some(corrected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::edit_distance;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("コンピュータ", "コンピューター"), 1);
+        assert_eq!(edit_distance("リンゴ", "リンゴ"), 0);
+        assert_eq!(edit_distance("東京", "京都"), 2);
+        assert_eq!(edit_distance("", "空港"), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_suggester() {
+        use tantivy::doc;
+        use tantivy::schema::Schema;
+
+        use crate::test_helpers::{create_index, ipadic_tokenizer, text_options};
+
+        use super::Suggester;
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", text_options());
+        let documents =
+            ["コンピューターを買う", "りんごを買う", "東京に行く"].map(|text| doc!(title => text));
+        let index = create_index(schema_builder.build(), documents);
+        let searcher = index.reader().unwrap().searcher();
+
+        let suggester = Suggester::new(ipadic_tokenizer(), &searcher, title).unwrap();
+        assert!(suggester.contains("コンピューター"));
+
+        let suggestions = suggester.suggest("コンピュータ", 3).unwrap();
+        assert_eq!(suggestions[0].text, "コンピューター");
+        assert_eq!(suggestions[0].distance, 1);
+
+        let suggestions = suggester.suggest("林檎", 3).unwrap();
+        assert_eq!(suggestions[0].text, "りんご");
+        assert_eq!(suggestions[0].distance, 0);
+
+        assert_eq!(
+            suggester.did_you_mean("コンピュータを買う").unwrap(),
+            Some("コンピューターを買う".to_string())
+        );
+        assert_eq!(suggester.did_you_mean("東京に行く").unwrap(), None);
+    }
+}
@@ -0,0 +1,61 @@
+//! Fixtures shared by the unit tests.
+
+use tantivy::schema::Schema;
+#[cfg(feature = "embed-ipadic")]
+use tantivy::schema::{IndexRecordOption, TextFieldIndexing, TextOptions};
+use tantivy::tokenizer::TextAnalyzer;
+use tantivy::{Index, IndexWriter, TantivyDocument};
+
+#[cfg(feature = "embed-ipadic")]
+use crate::tokenizer::LinderaTokenizer;
+
+/// The name the tokenizer is registered under in the test indexes.
+pub(crate) const TOKENIZER_NAME: &str = "lang_ja";
+
+/// Returns a tokenizer with the embedded IPADIC in normal mode.
+#[cfg(feature = "embed-ipadic")]
+pub(crate) fn ipadic_tokenizer() -> LinderaTokenizer {
+    use lindera::mode::Mode;
+
+    LinderaTokenizer::from_dictionary("embedded://ipadic", Mode::Normal, None).unwrap()
+}
+
+/// Returns the options of a text field indexed with [`TOKENIZER_NAME`].
+#[cfg(feature = "embed-ipadic")]
+pub(crate) fn text_options() -> TextOptions {
+    TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(TOKENIZER_NAME)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    )
+}
+
+/// Creates an in-RAM index with [`ipadic_tokenizer`] registered, and commits the documents.
+#[cfg(feature = "embed-ipadic")]
+pub(crate) fn create_index(
+    schema: Schema,
+    documents: impl IntoIterator<Item = TantivyDocument>,
+) -> Index {
+    create_index_with(schema, ipadic_tokenizer(), documents)
+}
+
+/// Creates an in-RAM index with the given tokenizer registered under [`TOKENIZER_NAME`],
+/// and commits the documents.
+pub(crate) fn create_index_with(
+    schema: Schema,
+    tokenizer: impl Into<TextAnalyzer>,
+    documents: impl IntoIterator<Item = TantivyDocument>,
+) -> Index {
+    let index = Index::create_in_ram(schema);
+    index
+        .tokenizers()
+        .register(TOKENIZER_NAME, tokenizer.into());
+
+    let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 50_000_000).unwrap();
+    for document in documents {
+        index_writer.add_document(document).unwrap();
+    }
+    index_writer.commit().unwrap();
+
+    index
+}
@@ -0,0 +1,23 @@
+//! Token filters for the Lindera tokenization pipeline.
+//!
+//! This module provides token filters that implement Lindera's
+//! [`TokenFilter`](lindera::token_filter::TokenFilter) trait. They are appended to a
+//! [`LinderaTokenizer`](crate::tokenizer::LinderaTokenizer) with
+//! [`append_token_filter`](crate::tokenizer::LinderaTokenizer::append_token_filter) and
+//! run after the filters that were appended before them.
+//!
+//! Several filters stack tokens: they insert alternative forms of a token (its romaji,
+//! pinyin, reading, decomposed parts, etc.) right after it, with the same position,
+//! position length and offsets, so that the alternatives match where the token does, as
+//! synonyms do. Tokens the filters have nothing to stack for, e.g. unknown words without a
+//! reading, are left alone.
+
+pub mod chinese_pinyin;
+pub mod chinese_script;
+pub mod decompound;
+pub mod edge_ngram;
+pub mod japanese_kana_fold;
+pub mod japanese_loanword;
+pub mod japanese_romaji;
+pub mod korean_jamo;
+pub mod tantivy_filter;
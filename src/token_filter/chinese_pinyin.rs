@@ -0,0 +1,229 @@
+//! Chinese pinyin token filter.
+//!
+//! This module provides the [`ChinesePinyinTokenFilter`], which stacks the pinyin of each
+//! Chinese token on the token, so that Chinese text can be searched with pinyin input.
+
+use std::borrow::Cow;
+
+use lindera::LinderaResult;
+use lindera::token::Token;
+use lindera::token_filter::TokenFilter;
+
+pub const CHINESE_PINYIN_TOKEN_FILTER_NAME: &str = "chinese_pinyin";
+
+/// A form of pinyin emitted by [`ChinesePinyinTokenFilter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PinyinForm {
+    /// Pinyin without tones, e.g. "jichang" for "机场".
+    Toneless,
+    /// Pinyin with tone numbers, e.g. "ji1chang3" for "机场".
+    Toned,
+    /// The first letter of each syllable, e.g. "jc" for "机场".
+    Initials,
+}
+
+/// Splits a CC-CEDICT pinyin into lowercase syllables, writing "ü" (or "u:") as "v".
+fn syllables(pinyin: &str) -> Vec<String> {
+    pinyin
+        .split_whitespace()
+        .map(|syllable| syllable.to_lowercase().replace("u:", "v").replace('ü', "v"))
+        .filter(|syllable| syllable.chars().any(|c| c.is_ascii_alphabetic()))
+        .collect()
+}
+
+/// Converts a CC-CEDICT pinyin (e.g. "ji1 chang3") to the given form.
+///
+/// The syllables are lowercased and concatenated, and "ü" is written as "v", as typed with
+/// a pinyin input method. Returns `None` if the pinyin has no syllables.
+///
+/// # Examples
+///
+/// ```
+/// use lindera_tantivy::token_filter::chinese_pinyin::{PinyinForm, convert_pinyin};
+///
+/// assert_eq!(convert_pinyin("Bei3 jing1", PinyinForm::Toneless).unwrap(), "beijing");
+/// assert_eq!(convert_pinyin("Bei3 jing1", PinyinForm::Toned).unwrap(), "bei3jing1");
+/// assert_eq!(convert_pinyin("Bei3 jing1", PinyinForm::Initials).unwrap(), "bj");
+/// ```
+pub fn convert_pinyin(pinyin: &str, form: PinyinForm) -> Option<String> {
+    let syllables = syllables(pinyin);
+    if syllables.is_empty() {
+        return None;
+    }
+
+    let converted = match form {
+        PinyinForm::Toneless => syllables
+            .iter()
+            .flat_map(|syllable| syllable.chars().filter(|c| !c.is_ascii_digit()))
+            .collect(),
+        PinyinForm::Toned => syllables.concat(),
+        PinyinForm::Initials => syllables
+            .iter()
+            .filter_map(|syllable| syllable.chars().next())
+            .collect(),
+    };
+    Some(converted)
+}
+
+/// A token filter that emits the pinyin of each token as stacked tokens.
+///
+/// The `pinyin` field of the CC-CEDICT entry of each token is converted to each of the
+/// configured [`PinyinForm`]s. Lowercased pinyin queries match the stacked forms as is.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::token_filter::chinese_pinyin::{ChinesePinyinTokenFilter, PinyinForm};
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dictionary = load_dictionary("embedded://cc-cedict")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// // "机场" is emitted together with "jichang" and "jc"
+/// tokenizer.append_token_filter(
+///     ChinesePinyinTokenFilter::new(vec![PinyinForm::Toneless, PinyinForm::Initials]).into(),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChinesePinyinTokenFilter {
+    forms: Vec<PinyinForm>,
+}
+
+impl ChinesePinyinTokenFilter {
+    /// Creates a new `ChinesePinyinTokenFilter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `forms` - The forms of pinyin to emit, in the order they are stacked
+    pub fn new(forms: Vec<PinyinForm>) -> Self {
+        Self { forms }
+    }
+
+    /// Returns the distinct pinyin forms of a token that differ from its surface.
+    fn forms(&self, token: &mut Token<'_>) -> Vec<String> {
+        let Some(pinyin) = token.get("pinyin") else {
+            return Vec::new();
+        };
+
+        let mut forms: Vec<String> = Vec::with_capacity(self.forms.len());
+        for form in &self.forms {
+            if let Some(converted) = convert_pinyin(pinyin, *form)
+                && !forms.contains(&converted)
+            {
+                forms.push(converted);
+            }
+        }
+        forms.retain(|form| form.as_str() != token.surface.as_ref());
+
+        forms
+    }
+}
+
+impl Default for ChinesePinyinTokenFilter {
+    fn default() -> Self {
+        Self::new(vec![
+            PinyinForm::Toneless,
+            PinyinForm::Toned,
+            PinyinForm::Initials,
+        ])
+    }
+}
+
+impl TokenFilter for ChinesePinyinTokenFilter {
+    fn name(&self) -> &'static str {
+        CHINESE_PINYIN_TOKEN_FILTER_NAME
+    }
+
+    /// Inserts the pinyin tokens after each token with pinyin.
+    fn apply(&self, tokens: &mut Vec<Token<'_>>) -> LinderaResult<()> {
+        let mut new_tokens = Vec::with_capacity(tokens.len() * (self.forms.len() + 1));
+        for mut token in tokens.drain(..) {
+            let stacked: Vec<Token<'_>> = self
+                .forms(&mut token)
+                .into_iter()
+                .map(|form| {
+                    let mut stacked = token.clone();
+                    stacked.surface = Cow::Owned(form);
+                    stacked
+                })
+                .collect();
+
+            new_tokens.push(token);
+            new_tokens.extend(stacked);
+        }
+        *tokens = new_tokens;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PinyinForm, convert_pinyin};
+
+    #[test]
+    fn test_convert_pinyin() {
+        assert_eq!(
+            convert_pinyin("ji1 chang3", PinyinForm::Toneless).unwrap(),
+            "jichang"
+        );
+        assert_eq!(
+            convert_pinyin("ji1 chang3", PinyinForm::Toned).unwrap(),
+            "ji1chang3"
+        );
+        assert_eq!(
+            convert_pinyin("ji1 chang3", PinyinForm::Initials).unwrap(),
+            "jc"
+        );
+        assert_eq!(
+            convert_pinyin("nu:3 er2", PinyinForm::Toneless).unwrap(),
+            "nver"
+        );
+        assert_eq!(convert_pinyin("lü4", PinyinForm::Toned).unwrap(), "lv4");
+        assert_eq!(convert_pinyin("", PinyinForm::Toneless), None);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-cc-cedict")]
+    fn test_chinese_pinyin_stacks_forms() {
+        use lindera::mode::Mode;
+        use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+        use crate::tokenizer::LinderaTokenizer;
+
+        use super::ChinesePinyinTokenFilter;
+        let mut tokenizer =
+            LinderaTokenizer::from_dictionary("embedded://cc-cedict", Mode::Normal, None).unwrap();
+        tokenizer.append_token_filter(ChinesePinyinTokenFilter::default().into());
+
+        let mut tokens: Vec<Token> = vec![];
+        tokenizer
+            .token_stream("北京机场")
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+
+        let texts: Vec<(&str, usize)> = tokens
+            .iter()
+            .map(|token| (token.text.as_str(), token.position))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                ("北京", 0),
+                ("beijing", 0),
+                ("bei3jing1", 0),
+                ("bj", 0),
+                ("机场", 1),
+                ("jichang", 1),
+                ("ji1chang3", 1),
+                ("jc", 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+//! Chinese script token filter.
+//!
+//! This module provides the [`ChineseScriptTokenFilter`], which normalizes Chinese tokens
+//! to Simplified or Traditional characters, or stacks both forms, so that "機場" and "机场"
+//! match each other in the same field.
+
+use std::borrow::Cow;
+
+use lindera::LinderaResult;
+use lindera::token::Token;
+use lindera::token_filter::TokenFilter;
+
+pub const CHINESE_SCRIPT_TOKEN_FILTER_NAME: &str = "chinese_script";
+
+/// How [`ChineseScriptTokenFilter`] handles the script of tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChineseScript {
+    /// Replaces tokens with their Simplified form.
+    #[default]
+    Simplified,
+    /// Replaces tokens with their Traditional form.
+    Traditional,
+    /// Keeps tokens as they are and stacks their form in the other script.
+    Both,
+}
+
+/// A token filter that normalizes Chinese tokens to one script.
+///
+/// The forms are taken from the `simplified` and `traditional` fields of the CC-CEDICT
+/// entry of each token, and either replace its surface or, with `ChineseScript::Both`, the
+/// form in the other script is stacked. Normalizing to one script needs the same filter
+/// on the query side.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::token_filter::chinese_script::{ChineseScript, ChineseScriptTokenFilter};
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dictionary = load_dictionary("embedded://cc-cedict")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// // "機場" is emitted as "机场"
+/// tokenizer.append_token_filter(ChineseScriptTokenFilter::new(ChineseScript::Simplified).into());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ChineseScriptTokenFilter {
+    script: ChineseScript,
+}
+
+impl ChineseScriptTokenFilter {
+    /// Creates a new `ChineseScriptTokenFilter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `script` - The script to normalize the tokens to, or `ChineseScript::Both` to
+    ///   stack both forms
+    pub fn new(script: ChineseScript) -> Self {
+        Self { script }
+    }
+}
+
+/// Returns a field of a token as an owned string.
+fn field(token: &mut Token<'_>, name: &str) -> Option<String> {
+    token
+        .get(name)
+        .filter(|value| !value.is_empty() && *value != "*")
+        .map(str::to_string)
+}
+
+impl TokenFilter for ChineseScriptTokenFilter {
+    fn name(&self) -> &'static str {
+        CHINESE_SCRIPT_TOKEN_FILTER_NAME
+    }
+
+    /// Replaces the surface of each token with the configured script, or stacks the form in
+    /// the other script after it.
+    fn apply(&self, tokens: &mut Vec<Token<'_>>) -> LinderaResult<()> {
+        let mut new_tokens = Vec::with_capacity(tokens.len());
+        for mut token in tokens.drain(..) {
+            match self.script {
+                ChineseScript::Simplified | ChineseScript::Traditional => {
+                    let name = if self.script == ChineseScript::Simplified {
+                        "simplified"
+                    } else {
+                        "traditional"
+                    };
+                    if let Some(form) = field(&mut token, name) {
+                        token.surface = Cow::Owned(form);
+                    }
+                    new_tokens.push(token);
+                }
+                ChineseScript::Both => {
+                    let mut forms = Vec::with_capacity(2);
+                    for name in ["simplified", "traditional"] {
+                        if let Some(form) = field(&mut token, name)
+                            && form != token.surface.as_ref()
+                            && !forms.contains(&form)
+                        {
+                            forms.push(form);
+                        }
+                    }
+
+                    let stacked: Vec<Token<'_>> = forms
+                        .into_iter()
+                        .map(|form| {
+                            let mut stacked = token.clone();
+                            stacked.surface = Cow::Owned(form);
+                            stacked
+                        })
+                        .collect();
+                    new_tokens.push(token);
+                    new_tokens.extend(stacked);
+                }
+            }
+        }
+        *tokens = new_tokens;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "embed-cc-cedict")]
+mod tests {
+    use lindera::mode::Mode;
+    use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+    use crate::tokenizer::LinderaTokenizer;
+
+    use super::{ChineseScript, ChineseScriptTokenFilter};
+
+    fn token_stream_helper(script: ChineseScript, text: &str) -> Vec<Token> {
+        let mut tokenizer =
+            LinderaTokenizer::from_dictionary("embedded://cc-cedict", Mode::Normal, None).unwrap();
+        tokenizer.append_token_filter(ChineseScriptTokenFilter::new(script).into());
+
+        let mut tokens: Vec<Token> = vec![];
+        tokenizer
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    fn texts(tokens: &[Token]) -> Vec<&str> {
+        tokens.iter().map(|token| token.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_chinese_script_simplified() {
+        let tokens = token_stream_helper(ChineseScript::Simplified, "機場");
+        assert_eq!(texts(&tokens), vec!["机场"]);
+        assert_eq!((tokens[0].offset_from, tokens[0].offset_to), (0, 6));
+    }
+
+    #[test]
+    fn test_chinese_script_traditional() {
+        let tokens = token_stream_helper(ChineseScript::Traditional, "机场");
+        assert_eq!(texts(&tokens), vec!["機場"]);
+    }
+
+    #[test]
+    fn test_chinese_script_both() {
+        let tokens = token_stream_helper(ChineseScript::Both, "机场");
+        assert_eq!(texts(&tokens), vec!["机场", "機場"]);
+        assert_eq!(tokens[0].position, tokens[1].position);
+    }
+}
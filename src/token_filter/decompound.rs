@@ -0,0 +1,285 @@
+//! Decompound token filter.
+//!
+//! This module provides the [`DecompoundTokenFilter`], which keeps compound tokens and
+//! emits their decomposed parts along them, similar to the search mode of Lucene's Kuromoji
+//! analyzer with original token retention.
+
+use std::borrow::Cow;
+
+use lindera::LinderaResult;
+use lindera::dictionary::Lattice;
+use lindera::mode::{Mode, Penalty};
+use lindera::token::Token;
+use lindera::token_filter::TokenFilter;
+
+pub const DECOMPOUND_TOKEN_FILTER_NAME: &str = "decompound";
+
+/// A character appended to the surface while decomposing it. It is in the SPACE category,
+/// so it is always segmented as a word of its own.
+const SENTINEL: char = ' ';
+
+/// A token filter that emits both compound tokens and their decomposed parts.
+///
+/// Each token is segmented again with Lindera's [`Mode::Decompose`] using the dictionary
+/// that produced it. When the token splits into several parts, the parts get consecutive
+/// positions starting at the position of the compound token, and the compound token gets a
+/// `position_length` spanning all of them, as in Kuromoji. Both "関西国際空港" and "空港"
+/// can be matched, and so can phrases made of the parts, e.g. "国際 空港".
+///
+/// Tantivy ignores `position_length`, so the tokens after a compound are shifted by its
+/// extra parts, and a phrase going through the compound token itself only matches with
+/// enough slop: "関西国際空港 限定" needs a slop of 2, as "限定" follows "空港".
+///
+/// The segmenter of the tokenizer should be in [`Mode::Normal`] so that compounds are
+/// kept as single tokens before this filter runs. The filter is meant for the indexing
+/// side; the query side usually keeps the plain tokenizer.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::token_filter::decompound::DecompoundTokenFilter;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// // "関西国際空港" is emitted together with "関西", "国際" and "空港"
+/// tokenizer.append_token_filter(DecompoundTokenFilter::default().into());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DecompoundTokenFilter {
+    mode: Mode,
+}
+
+impl DecompoundTokenFilter {
+    /// Creates a new `DecompoundTokenFilter` using the given penalty to decompose tokens.
+    ///
+    /// # Arguments
+    ///
+    /// * `penalty` - The penalty of Lindera's decompose mode that controls how eagerly
+    ///   long tokens are split
+    pub fn new(penalty: Penalty) -> Self {
+        Self {
+            mode: Mode::Decompose(penalty),
+        }
+    }
+
+    /// Segments a token in decompose mode and returns its parts.
+    ///
+    /// Returns an empty vector if the token does not split into two or more parts.
+    fn decompose<'a>(&self, token: &Token<'a>, lattice: &mut Lattice) -> Vec<Token<'a>> {
+        let surface: &str = token.surface.as_ref();
+        if surface.is_empty() {
+            return Vec::new();
+        }
+
+        // Lindera's lattice applies the decompose penalty to a word when connecting it to the
+        // next word, but not when connecting it to EOS, so a surface segmented alone would
+        // never be split. The edges of the lattice cannot be rescored from outside, so a
+        // sentinel word is appended to let the last word of the surface be penalized too.
+        let text = format!("{surface}{SENTINEL}");
+
+        let dictionary = token.dictionary;
+        lattice.set_text(
+            &dictionary.prefix_dictionary,
+            &token.user_dictionary.map(|d| &d.dict),
+            &dictionary.character_definition,
+            &dictionary.unknown_dictionary,
+            &dictionary.connection_cost_matrix,
+            &text,
+            &self.mode,
+        );
+
+        // The sentinel must have been segmented on its own for the other words to be the
+        // parts of the surface.
+        let mut offsets = lattice.tokens_offset();
+        if offsets.pop().map(|(start, _)| start) != Some(surface.len()) || offsets.len() < 2 {
+            return Vec::new();
+        }
+
+        // Part offsets can only be mapped back to the original text if no character
+        // filter has changed the length of the token.
+        let aligned = token.byte_end - token.byte_start == surface.len();
+
+        let mut parts = Vec::with_capacity(offsets.len());
+        for (i, (start, word_id)) in offsets.iter().enumerate() {
+            let end = offsets
+                .get(i + 1)
+                .map_or(surface.len(), |(next_start, _)| *next_start);
+
+            let part_surface = match &token.surface {
+                Cow::Borrowed(s) => Cow::Borrowed(&s[*start..end]),
+                Cow::Owned(s) => Cow::Owned(s[*start..end].to_string()),
+            };
+            let (byte_start, byte_end) = if aligned {
+                (token.byte_start + start, token.byte_start + end)
+            } else {
+                (token.byte_start, token.byte_end)
+            };
+
+            parts.push(Token::new(
+                part_surface,
+                byte_start,
+                byte_end,
+                token.position + i,
+                *word_id,
+                dictionary,
+                token.user_dictionary,
+            ));
+        }
+
+        parts
+    }
+}
+
+impl Default for DecompoundTokenFilter {
+    fn default() -> Self {
+        Self::new(Penalty::default())
+    }
+}
+
+impl TokenFilter for DecompoundTokenFilter {
+    fn name(&self) -> &'static str {
+        DECOMPOUND_TOKEN_FILTER_NAME
+    }
+
+    /// Inserts the decomposed parts after each compound token, and shifts the positions of
+    /// the following tokens by the extra parts.
+    fn apply(&self, tokens: &mut Vec<Token<'_>>) -> LinderaResult<()> {
+        let mut lattice = Lattice::default();
+
+        // The shift grows once per position, however many tokens are stacked on it.
+        let mut shift = 0;
+        let mut extra = 0;
+        let mut current = None;
+
+        let mut new_tokens = Vec::with_capacity(tokens.len());
+        for mut token in tokens.drain(..) {
+            if current != Some(token.position) {
+                current = Some(token.position);
+                shift += extra;
+                extra = 0;
+            }
+            token.position += shift;
+
+            let parts = self.decompose(&token, &mut lattice);
+            if !parts.is_empty() {
+                extra = extra.max(parts.len() - 1);
+                token.position_length += parts.len() - 1;
+            }
+            new_tokens.push(token);
+            new_tokens.extend(parts);
+        }
+        *tokens = new_tokens;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "embed-ipadic")]
+mod tests {
+    use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+    use crate::test_helpers::ipadic_tokenizer;
+
+    use super::DecompoundTokenFilter;
+
+    fn token_stream_helper(text: &str) -> Vec<Token> {
+        let mut tokenizer = ipadic_tokenizer();
+        tokenizer.append_token_filter(DecompoundTokenFilter::default().into());
+
+        let mut token_stream = tokenizer.token_stream(text);
+        let mut tokens: Vec<Token> = vec![];
+        token_stream.process(&mut |token: &Token| tokens.push(token.clone()));
+
+        tokens
+    }
+
+    fn assert_token(token: &Token, position: usize, position_length: usize, text: &str) {
+        assert_eq!(
+            token.position, position,
+            "expected position {position} but {token:?}"
+        );
+        assert_eq!(
+            token.position_length, position_length,
+            "expected position_length {position_length} but {token:?}"
+        );
+        assert_eq!(token.text, text, "expected text {text} but {token:?}");
+    }
+
+    #[test]
+    fn test_decompound_keeps_compound_and_parts() {
+        let tokens = token_stream_helper("関西国際空港限定");
+        assert_eq!(tokens.len(), 5);
+        assert_token(&tokens[0], 0, 3, "関西国際空港");
+        assert_token(&tokens[1], 0, 1, "関西");
+        assert_token(&tokens[2], 1, 1, "国際");
+        assert_token(&tokens[3], 2, 1, "空港");
+        assert_token(&tokens[4], 3, 1, "限定");
+        assert_eq!((tokens[3].offset_from, tokens[3].offset_to), (12, 18));
+    }
+
+    #[test]
+    fn test_decompound_phrase() {
+        use tantivy::collector::Count;
+        use tantivy::query::{PhraseQuery, TermQuery};
+        use tantivy::schema::{IndexRecordOption, Schema};
+        use tantivy::{Term, doc};
+
+        use crate::test_helpers::{create_index_with, text_options};
+
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", text_options());
+        let mut tokenizer = ipadic_tokenizer();
+        tokenizer.append_token_filter(DecompoundTokenFilter::default().into());
+        let index = create_index_with(
+            schema_builder.build(),
+            tokenizer,
+            [doc!(text => "羽田から関西国際空港限定の便")],
+        );
+        let searcher = index.reader().unwrap().searcher();
+
+        let phrase_count = |words: &[&str], slop: u32| {
+            let terms = words
+                .iter()
+                .map(|word| Term::from_field_text(text, word))
+                .collect();
+            let mut query = PhraseQuery::new(terms);
+            query.set_slop(slop);
+            searcher.search(&query, &Count).unwrap()
+        };
+        // The parts follow each other, and the following tokens are shifted by them.
+        assert_eq!(
+            phrase_count(&["から", "関西", "国際", "空港", "限定"], 0),
+            1
+        );
+        assert_eq!(phrase_count(&["国際", "空港"], 0), 1);
+        assert_eq!(phrase_count(&["限定", "の", "便"], 0), 1);
+        // Tantivy ignores the position length of the compound token.
+        assert_eq!(phrase_count(&["から", "関西国際空港"], 0), 1);
+        assert_eq!(phrase_count(&["関西国際空港", "限定"], 0), 0);
+        assert_eq!(phrase_count(&["関西国際空港", "限定"], 2), 1);
+
+        let query = TermQuery::new(
+            Term::from_field_text(text, "国際"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_decompound_ignores_short_tokens() {
+        let tokens = token_stream_helper("空港限定");
+        assert_eq!(tokens.len(), 2);
+        assert_token(&tokens[0], 0, 1, "空港");
+        assert_token(&tokens[1], 1, 1, "限定");
+    }
+}
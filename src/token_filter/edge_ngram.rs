@@ -0,0 +1,161 @@
+//! Edge n-gram token filter.
+//!
+//! This module provides the [`EdgeNgramTokenFilter`], which replaces each token with the
+//! prefixes of its surface and reading, for search-as-you-type on morphological tokens.
+
+use std::borrow::Cow;
+
+use lindera::LinderaResult;
+use lindera::token::Token;
+use lindera::token_filter::TokenFilter;
+use lindera::token_filter::japanese_kana::KanaKind;
+use tantivy::{Result, TantivyError};
+
+use crate::token_filter::japanese_kana_fold::{fold_kana, is_reading};
+
+pub const EDGE_NGRAM_TOKEN_FILTER_NAME: &str = "edge_ngram";
+
+/// A token filter that emits the edge n-grams of each token's surface and reading.
+///
+/// Each token is replaced with the stacked prefixes of its surface that are between
+/// `min_gram` and `max_gram` characters long. When readings are included, the prefixes of
+/// the katakana reading and of its hiragana form are added too, so that a partially typed
+/// kana input such as "とうき" matches "東京".
+///
+/// The filter belongs to the indexing side of an autocomplete field, which
+/// [`index_tokenizer`](crate::autocomplete::index_tokenizer) builds; the query side looks up
+/// the raw input with [`autocomplete_query`](crate::autocomplete::autocomplete_query).
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::token_filter::edge_ngram::EdgeNgramTokenFilter;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// // "東京" is emitted as "東", "東京", "ト", "トウ", ..., "と", "とう", ...
+/// tokenizer.append_token_filter(EdgeNgramTokenFilter::new(1, 10, true)?.into());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct EdgeNgramTokenFilter {
+    min_gram: usize,
+    max_gram: usize,
+    include_reading: bool,
+}
+
+impl EdgeNgramTokenFilter {
+    /// Creates a new `EdgeNgramTokenFilter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `min_gram` - The minimum length of the n-grams, in characters
+    /// * `max_gram` - The maximum length of the n-grams, in characters
+    /// * `include_reading` - Whether to emit the n-grams of the reading of each token
+    ///
+    /// # Returns
+    ///
+    /// Returns the filter, or an error if `min_gram` is 0 or greater than `max_gram`.
+    pub fn new(min_gram: usize, max_gram: usize, include_reading: bool) -> Result<Self> {
+        if min_gram == 0 || min_gram > max_gram {
+            return Err(TantivyError::InvalidArgument(format!(
+                "invalid n-gram lengths: min_gram {min_gram}, max_gram {max_gram}"
+            )));
+        }
+
+        Ok(Self {
+            min_gram,
+            max_gram,
+            include_reading,
+        })
+    }
+
+    /// Appends the edge n-grams of a text to `grams`, skipping those already in it.
+    fn push_ngrams(&self, text: &str, grams: &mut Vec<String>) {
+        for (count, (offset, c)) in text.char_indices().enumerate() {
+            let length = count + 1;
+            if length > self.max_gram {
+                break;
+            }
+            if length < self.min_gram {
+                continue;
+            }
+
+            let gram = &text[..offset + c.len_utf8()];
+            if !grams.iter().any(|existing| existing == gram) {
+                grams.push(gram.to_string());
+            }
+        }
+    }
+}
+
+impl TokenFilter for EdgeNgramTokenFilter {
+    fn name(&self) -> &'static str {
+        EDGE_NGRAM_TOKEN_FILTER_NAME
+    }
+
+    /// Replaces each token with its edge n-grams.
+    ///
+    /// Tokens shorter than `min_gram` without a long enough reading are removed.
+    fn apply(&self, tokens: &mut Vec<Token<'_>>) -> LinderaResult<()> {
+        let mut new_tokens = Vec::with_capacity(tokens.len() * self.max_gram);
+        for mut token in tokens.drain(..) {
+            let mut grams = Vec::new();
+            self.push_ngrams(&token.surface, &mut grams);
+
+            if self.include_reading
+                && let Some(reading) = token.get("reading").filter(|r| is_reading(r))
+            {
+                let reading = reading.to_string();
+                self.push_ngrams(&reading, &mut grams);
+                self.push_ngrams(&fold_kana(&reading, &KanaKind::Hiragana), &mut grams);
+            }
+
+            new_tokens.extend(grams.into_iter().map(|gram| {
+                let mut gram_token = token.clone();
+                gram_token.surface = Cow::Owned(gram);
+                gram_token
+            }));
+        }
+        *tokens = new_tokens;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EdgeNgramTokenFilter;
+
+    fn ngrams(filter: &EdgeNgramTokenFilter, text: &str) -> Vec<String> {
+        let mut grams = Vec::new();
+        filter.push_ngrams(text, &mut grams);
+        grams
+    }
+
+    #[test]
+    fn test_edge_ngrams() {
+        let filter = EdgeNgramTokenFilter::new(1, 3, false).unwrap();
+        assert_eq!(ngrams(&filter, "トウキョウ"), vec!["ト", "トウ", "トウキ"]);
+        assert_eq!(ngrams(&filter, "空"), vec!["空"]);
+
+        let filter = EdgeNgramTokenFilter::new(2, 10, false).unwrap();
+        assert_eq!(ngrams(&filter, "空港"), vec!["空港"]);
+        assert!(ngrams(&filter, "空").is_empty());
+    }
+
+    #[test]
+    fn test_edge_ngram_lengths() {
+        assert!(EdgeNgramTokenFilter::new(0, 3, false).is_err());
+        assert!(EdgeNgramTokenFilter::new(4, 3, false).is_err());
+        assert!(EdgeNgramTokenFilter::new(3, 3, false).is_ok());
+    }
+}
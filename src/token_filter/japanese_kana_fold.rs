@@ -0,0 +1,214 @@
+//! Japanese kana folding token filter.
+//!
+//! This module provides the [`JapaneseKanaFoldTokenFilter`], which folds hiragana and
+//! katakana into one kana type and optionally stacks the reading of each token, so that
+//! "りんご", "リンゴ" and "林檎" match each other.
+
+use std::borrow::Cow;
+
+use lindera::LinderaResult;
+use lindera::token::Token;
+use lindera::token_filter::TokenFilter;
+use lindera::token_filter::japanese_kana::KanaKind;
+
+pub const JAPANESE_KANA_FOLD_TOKEN_FILTER_NAME: &str = "japanese_kana_fold";
+
+/// The distance between a hiragana and the corresponding katakana in Unicode.
+const KANA_OFFSET: u32 = 0x60;
+
+/// Folds the hiragana or katakana of a text into the given kana type.
+///
+/// Only the kana that exist in both scripts are folded; the prolonged sound mark and
+/// katakana without a hiragana counterpart (e.g. "ヷ") are kept.
+///
+/// # Examples
+///
+/// ```
+/// use lindera::token_filter::japanese_kana::KanaKind;
+/// use lindera_tantivy::token_filter::japanese_kana_fold::fold_kana;
+///
+/// assert_eq!(fold_kana("りんごジュース", &KanaKind::Katakana), "リンゴジュース");
+/// assert_eq!(fold_kana("りんごジュース", &KanaKind::Hiragana), "りんごじゅーす");
+/// ```
+pub fn fold_kana(text: &str, kind: &KanaKind) -> String {
+    text.chars()
+        .map(|c| {
+            let code = c as u32;
+            let folded = match kind {
+                KanaKind::Katakana if ('ぁ'..='ゖ').contains(&c) => code + KANA_OFFSET,
+                KanaKind::Hiragana if ('ァ'..='ヶ').contains(&c) => code - KANA_OFFSET,
+                _ => code,
+            };
+            char::from_u32(folded).unwrap_or(c)
+        })
+        .collect()
+}
+
+/// Returns whether a text is a katakana reading.
+pub(crate) fn is_reading(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| ('ァ'..='ヺ').contains(&c) || c == 'ー')
+}
+
+/// A token filter that folds hiragana and katakana into one kana type.
+///
+/// The surface of each token is folded with [`fold_kana`], and the folded reading can be
+/// stacked, so "林檎" is also indexed as "リンゴ". On the query side, Lindera's
+/// `JapaneseReadingFormTokenFilter` followed by this filter without stacking reduces a
+/// query in any form to the folded reading.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera::token_filter::japanese_kana::KanaKind;
+/// use lindera::token_filter::japanese_reading_form::JapaneseReadingFormTokenFilter;
+/// use lindera_tantivy::token_filter::japanese_kana_fold::JapaneseKanaFoldTokenFilter;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+///
+/// // Index side: "りんご" is emitted as "リンゴ", "林檎" as "林檎" and "リンゴ"
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary.clone(), None);
+/// let mut index_tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+/// index_tokenizer
+///     .append_token_filter(JapaneseKanaFoldTokenFilter::new(KanaKind::Katakana, true).into());
+///
+/// // Query side: "りんご", "リンゴ" and "林檎" are all emitted as "リンゴ"
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let mut query_tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+/// query_tokenizer
+///     .append_token_filter(JapaneseReadingFormTokenFilter::new().into())
+///     .append_token_filter(JapaneseKanaFoldTokenFilter::new(KanaKind::Katakana, false).into());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct JapaneseKanaFoldTokenFilter {
+    kind: KanaKind,
+    stack_reading: bool,
+}
+
+impl JapaneseKanaFoldTokenFilter {
+    /// Creates a new `JapaneseKanaFoldTokenFilter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kana type to fold hiragana and katakana into
+    /// * `stack_reading` - Whether to stack the folded reading of each token on the token
+    pub fn new(kind: KanaKind, stack_reading: bool) -> Self {
+        Self {
+            kind,
+            stack_reading,
+        }
+    }
+}
+
+impl TokenFilter for JapaneseKanaFoldTokenFilter {
+    fn name(&self) -> &'static str {
+        JAPANESE_KANA_FOLD_TOKEN_FILTER_NAME
+    }
+
+    /// Folds the surface of each token, and inserts the folded reading after it if enabled.
+    fn apply(&self, tokens: &mut Vec<Token<'_>>) -> LinderaResult<()> {
+        let mut new_tokens = Vec::with_capacity(tokens.len());
+        for mut token in tokens.drain(..) {
+            let reading = if self.stack_reading {
+                token
+                    .get("reading")
+                    .filter(|reading| is_reading(reading))
+                    .map(|reading| fold_kana(reading, &self.kind))
+            } else {
+                None
+            };
+
+            let surface = fold_kana(&token.surface, &self.kind);
+            if surface != token.surface.as_ref() {
+                token.surface = Cow::Owned(surface);
+            }
+
+            let stacked = reading
+                .filter(|reading| reading.as_str() != token.surface.as_ref())
+                .map(|reading| {
+                    let mut stacked = token.clone();
+                    stacked.surface = Cow::Owned(reading);
+                    stacked
+                });
+            new_tokens.push(token);
+            new_tokens.extend(stacked);
+        }
+        *tokens = new_tokens;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lindera::token_filter::japanese_kana::KanaKind;
+
+    use super::fold_kana;
+
+    #[test]
+    fn test_fold_kana() {
+        assert_eq!(fold_kana("りんご", &KanaKind::Katakana), "リンゴ");
+        assert_eq!(fold_kana("リンゴ", &KanaKind::Hiragana), "りんご");
+        assert_eq!(fold_kana("林檎", &KanaKind::Katakana), "林檎");
+        assert_eq!(
+            fold_kana("ヴァイオリン", &KanaKind::Hiragana),
+            "ゔぁいおりん"
+        );
+        assert_eq!(fold_kana("コーヒー", &KanaKind::Hiragana), "こーひー");
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_japanese_kana_fold_search() {
+        use lindera::token_filter::japanese_reading_form::JapaneseReadingFormTokenFilter;
+        use tantivy::collector::Count;
+        use tantivy::doc;
+        use tantivy::query::QueryParser;
+        use tantivy::schema::Schema;
+        use tantivy::tokenizer::TokenizerManager;
+
+        use crate::test_helpers::{
+            TOKENIZER_NAME, create_index_with, ipadic_tokenizer, text_options,
+        };
+
+        use super::JapaneseKanaFoldTokenFilter;
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", text_options());
+        let schema = schema_builder.build();
+
+        let mut index_tokenizer = ipadic_tokenizer();
+        index_tokenizer
+            .append_token_filter(JapaneseKanaFoldTokenFilter::new(KanaKind::Katakana, true).into());
+        let index = create_index_with(
+            schema.clone(),
+            index_tokenizer,
+            ["りんご", "リンゴ", "林檎"].map(|text| doc!(title => text)),
+        );
+
+        let mut query_tokenizer = ipadic_tokenizer();
+        query_tokenizer
+            .append_token_filter(JapaneseReadingFormTokenFilter::new().into())
+            .append_token_filter(
+                JapaneseKanaFoldTokenFilter::new(KanaKind::Katakana, false).into(),
+            );
+        let query_tokenizers = TokenizerManager::default();
+        query_tokenizers.register(TOKENIZER_NAME, query_tokenizer);
+        let query_parser = QueryParser::new(schema, vec![title], query_tokenizers);
+
+        let searcher = index.reader().unwrap().searcher();
+        for text in ["りんご", "リンゴ", "林檎"] {
+            let query = query_parser.parse_query(text).unwrap();
+            assert_eq!(searcher.search(&query, &Count).unwrap(), 3, "query {text}");
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Japanese loanword normalization token filter.
+//!
+//! This module provides the [`JapaneseLoanwordTokenFilter`], which normalizes the spelling
+//! variants of katakana loanwords, so that "ヴァイオリン", "バイオリン" and "ｳﾞｧｲｵﾘﾝ" match
+//! each other.
+
+use std::borrow::Cow;
+use std::iter::Peekable;
+use std::str::Chars;
+
+use lindera::LinderaResult;
+use lindera::token::Token;
+use lindera::token_filter::TokenFilter;
+
+pub const JAPANESE_LOANWORD_TOKEN_FILTER_NAME: &str = "japanese_loanword";
+
+/// The full-width katakana corresponding to the half-width katakana from "ｦ" to "ﾝ".
+const HALF_WIDTH_KATAKANA: &str = "ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン";
+
+/// Converts a half-width katakana to full-width.
+fn widen(c: char) -> char {
+    match c {
+        'ｦ'..='ﾝ' => HALF_WIDTH_KATAKANA
+            .chars()
+            .nth((c as u32 - 'ｦ' as u32) as usize)
+            .unwrap_or(c),
+        'ﾞ' => '゛',
+        'ﾟ' => '゜',
+        _ => c,
+    }
+}
+
+/// Returns the voiced form of a katakana, e.g. "ガ" for "カ".
+fn voiced(c: char) -> Option<char> {
+    match c {
+        'ウ' => Some('ヴ'),
+        'ワ' => Some('ヷ'),
+        'ヲ' => Some('ヺ'),
+        _ if "カキクケコサシスセソタチツテトハヒフヘホ".contains(c) => {
+            char::from_u32(c as u32 + 1)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the semi-voiced form of a katakana, e.g. "パ" for "ハ".
+fn semi_voiced(c: char) -> Option<char> {
+    match c {
+        'ハ' | 'ヒ' | 'フ' | 'ヘ' | 'ホ' => char::from_u32(c as u32 + 2),
+        _ => None,
+    }
+}
+
+/// Returns whether a character is a variant of the prolonged sound mark when it follows
+/// a katakana.
+fn is_long_mark(c: char) -> bool {
+    matches!(
+        c,
+        'ー' | '－' | '−' | '‐' | '‑' | '–' | '—' | '―' | '～' | '〜'
+    )
+}
+
+/// Returns whether a character is a full-width katakana or the prolonged sound mark.
+fn is_katakana(c: char) -> bool {
+    ('ァ'..='ヺ').contains(&c) || c == 'ー'
+}
+
+/// Returns whether a text is a katakana word, made of full-width or half-width katakana,
+/// sound marks and variants of the prolonged sound mark.
+fn is_katakana_word(text: &str) -> bool {
+    let mut chars = text.chars().map(widen);
+    chars.next().is_some_and(is_katakana)
+        && chars.all(|c| is_katakana(c) || is_long_mark(c) || matches!(c, '゛' | '゜'))
+}
+
+/// Returns the large form of a small katakana, e.g. "ア" for "ァ". The small "ッ" is a
+/// geminate consonant rather than a small "ツ", so it is kept.
+fn large(c: char) -> char {
+    match c {
+        'ァ' => 'ア',
+        'ィ' => 'イ',
+        'ゥ' => 'ウ',
+        'ェ' => 'エ',
+        'ォ' => 'オ',
+        'ャ' => 'ヤ',
+        'ュ' => 'ユ',
+        'ョ' => 'ヨ',
+        'ヮ' => 'ワ',
+        'ヵ' => 'カ',
+        'ヶ' => 'ケ',
+        _ => c,
+    }
+}
+
+/// Reads the next character, converting half-width katakana to full-width and combining
+/// it with a following half-width voiced or semi-voiced sound mark.
+fn next_char(chars: &mut Peekable<Chars<'_>>) -> Option<char> {
+    let c = widen(chars.next()?);
+    let combined = match chars.peek() {
+        Some('ﾞ') => voiced(c),
+        Some('ﾟ') => semi_voiced(c),
+        _ => None,
+    };
+    if combined.is_some() {
+        chars.next();
+    }
+    combined.or(Some(c))
+}
+
+/// Normalizes the spelling variants of a katakana loanword.
+///
+/// Text that is not a katakana word, e.g. "東京－大阪", is returned as is. The
+/// normalization:
+///
+/// * converts half-width katakana to full-width,
+/// * spells the "ヴ" row with the "バ" row, e.g. "ヴァ" as "バ" and "ヴュ" as "ビュ",
+/// * replaces the dashes and wave dashes following a katakana with the prolonged sound
+///   mark "ー", and collapses repeated prolonged sound marks,
+/// * converts small katakana other than "ッ" to large, e.g. "ァ" to "ア", if `fold_small`
+///   is set.
+///
+/// # Examples
+///
+/// ```
+/// use lindera_tantivy::token_filter::japanese_loanword::normalize_loanword;
+///
+/// assert_eq!(normalize_loanword("ヴァイオリン", false), "バイオリン");
+/// assert_eq!(normalize_loanword("ｳﾞｧｲｵﾘﾝ", false), "バイオリン");
+/// assert_eq!(normalize_loanword("コーヒ－－", false), "コーヒー");
+/// assert_eq!(normalize_loanword("フィルム", true), "フイルム");
+/// assert_eq!(normalize_loanword("東京－大阪", false), "東京－大阪");
+/// ```
+pub fn normalize_loanword(text: &str, fold_small: bool) -> String {
+    if !is_katakana_word(text) {
+        return text.to_string();
+    }
+
+    let mut normalized = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = next_char(&mut chars) {
+        if is_long_mark(c) && normalized.chars().next_back().is_some_and(is_katakana) {
+            if !normalized.ends_with('ー') {
+                normalized.push('ー');
+            }
+            continue;
+        }
+
+        match c {
+            'ヴ' => {
+                let next = chars.peek().copied().map(widen);
+                let replacement = match next {
+                    Some('ァ') => 'バ',
+                    Some('ィ' | 'ュ') => 'ビ',
+                    Some('ゥ') => 'ブ',
+                    Some('ェ') => 'ベ',
+                    Some('ォ') => 'ボ',
+                    _ => 'ブ',
+                };
+                // The small vowel is part of the replacement, but "ュ" is kept in "ビュ".
+                if matches!(next, Some('ァ' | 'ィ' | 'ゥ' | 'ェ' | 'ォ')) {
+                    chars.next();
+                }
+                normalized.push(replacement);
+            }
+            'ヷ' => normalized.push('バ'),
+            'ヸ' => normalized.push('ビ'),
+            'ヹ' => normalized.push('ベ'),
+            'ヺ' => normalized.push('ボ'),
+            _ if fold_small => normalized.push(large(c)),
+            _ => normalized.push(c),
+        }
+    }
+
+    normalized
+}
+
+/// A token filter that normalizes the spelling variants of katakana loanwords.
+///
+/// The surface of each katakana token is normalized with [`normalize_loanword`], and the
+/// normalized form either replaces the surface or is stacked, so the original spelling
+/// still matches exactly. Other tokens are left as is. Folding small katakana also merges
+/// "キャリア" and "キヤリア", so it is off by default. Queries should be normalized the same
+/// way, without stacking.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::token_filter::japanese_loanword::JapaneseLoanwordTokenFilter;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+///
+/// // Index side: "ヴァイオリン" is emitted as "ヴァイオリン" and "バイオリン"
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary.clone(), None);
+/// let mut index_tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+/// index_tokenizer.append_token_filter(JapaneseLoanwordTokenFilter::new(false, true).into());
+///
+/// // Query side: "ヴァイオリン" is emitted as "バイオリン"
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let mut query_tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+/// query_tokenizer.append_token_filter(JapaneseLoanwordTokenFilter::new(false, false).into());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct JapaneseLoanwordTokenFilter {
+    fold_small: bool,
+    stack: bool,
+}
+
+impl JapaneseLoanwordTokenFilter {
+    /// Creates a new `JapaneseLoanwordTokenFilter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `fold_small` - Whether to convert small katakana to large
+    /// * `stack` - Whether to stack the normalized form on the token instead of replacing
+    ///   the surface
+    pub fn new(fold_small: bool, stack: bool) -> Self {
+        Self { fold_small, stack }
+    }
+}
+
+impl Default for JapaneseLoanwordTokenFilter {
+    /// Creates a filter that replaces the surface without folding small katakana.
+    fn default() -> Self {
+        Self::new(false, false)
+    }
+}
+
+impl TokenFilter for JapaneseLoanwordTokenFilter {
+    fn name(&self) -> &'static str {
+        JAPANESE_LOANWORD_TOKEN_FILTER_NAME
+    }
+
+    /// Normalizes the surface of each token, in place or by inserting a stacked token.
+    fn apply(&self, tokens: &mut Vec<Token<'_>>) -> LinderaResult<()> {
+        let mut new_tokens = Vec::with_capacity(tokens.len());
+        for mut token in tokens.drain(..) {
+            let normalized = normalize_loanword(&token.surface, self.fold_small);
+            if normalized == token.surface.as_ref() {
+                new_tokens.push(token);
+            } else if self.stack {
+                let mut stacked = token.clone();
+                stacked.surface = Cow::Owned(normalized);
+                new_tokens.push(token);
+                new_tokens.push(stacked);
+            } else {
+                token.surface = Cow::Owned(normalized);
+                new_tokens.push(token);
+            }
+        }
+        *tokens = new_tokens;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_loanword;
+
+    #[test]
+    fn test_normalize_loanword() {
+        assert_eq!(normalize_loanword("ヴァイオリン", false), "バイオリン");
+        assert_eq!(normalize_loanword("ヴィーナス", false), "ビーナス");
+        assert_eq!(normalize_loanword("ヴュー", false), "ビュー");
+        assert_eq!(normalize_loanword("ヴォーカル", false), "ボーカル");
+        assert_eq!(normalize_loanword("ｳﾞｧｲｵﾘﾝ", false), "バイオリン");
+        assert_eq!(normalize_loanword("ﾊﾟｿｺﾝ", false), "パソコン");
+        assert_eq!(
+            normalize_loanword("コンピューター", false),
+            "コンピューター"
+        );
+        assert_eq!(
+            normalize_loanword("コンピュ－タ－", false),
+            "コンピューター"
+        );
+        assert_eq!(normalize_loanword("ス〜パ〜", false), "スーパー");
+        assert_eq!(normalize_loanword("ワーーイ", false), "ワーイ");
+        assert_eq!(normalize_loanword("ファイル", false), "ファイル");
+        assert_eq!(normalize_loanword("ファイル", true), "フアイル");
+        assert_eq!(normalize_loanword("ヴァッハ", true), "バッハ");
+        assert_eq!(normalize_loanword("ｷｬｯｼｭ", true), "キヤッシユ");
+        // Only katakana words are normalized.
+        assert_eq!(normalize_loanword("東京－大阪", false), "東京－大阪");
+        assert_eq!(normalize_loanword("ＴＣＰ－ＩＰ", false), "ＴＣＰ－ＩＰ");
+        assert_eq!(
+            normalize_loanword("ヴァイオリン弾き", false),
+            "ヴァイオリン弾き"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_japanese_loanword_search() {
+        use tantivy::collector::Count;
+        use tantivy::doc;
+        use tantivy::query::QueryParser;
+        use tantivy::schema::Schema;
+        use tantivy::tokenizer::TokenizerManager;
+
+        use crate::test_helpers::{
+            TOKENIZER_NAME, create_index_with, ipadic_tokenizer, text_options,
+        };
+
+        use super::JapaneseLoanwordTokenFilter;
+
+        let tokenizer = |stack: bool| {
+            let mut tokenizer = ipadic_tokenizer();
+            tokenizer.append_token_filter(JapaneseLoanwordTokenFilter::new(false, stack).into());
+            tokenizer
+        };
+
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", text_options());
+        let schema = schema_builder.build();
+        let index = create_index_with(
+            schema.clone(),
+            tokenizer(true),
+            ["ヴァイオリン", "バイオリン"].map(|text| doc!(title => text)),
+        );
+
+        let query_tokenizers = TokenizerManager::default();
+        query_tokenizers.register(TOKENIZER_NAME, tokenizer(false));
+        let query_parser = QueryParser::new(schema, vec![title], query_tokenizers);
+
+        let searcher = index.reader().unwrap().searcher();
+        for text in ["ヴァイオリン", "バイオリン"] {
+            let query = query_parser.parse_query(text).unwrap();
+            assert_eq!(searcher.search(&query, &Count).unwrap(), 2, "query {text}");
+        }
+    }
+}
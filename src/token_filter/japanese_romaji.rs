@@ -0,0 +1,189 @@
+//! Japanese romaji token filter.
+//!
+//! This module provides the [`JapaneseRomajiTokenFilter`], which stacks the romaji of each
+//! token's reading on the token, so that romaji queries match Japanese text.
+
+use std::borrow::Cow;
+
+use lindera::LinderaResult;
+use lindera::token::Token;
+use lindera::token_filter::TokenFilter;
+
+use crate::romaji::{RomajiSystem, normalize_romaji, to_romaji};
+
+pub const JAPANESE_ROMAJI_TOKEN_FILTER_NAME: &str = "japanese_romaji";
+
+/// A token filter that emits the romaji of each token's reading as a stacked token.
+///
+/// The katakana reading is transliterated to Hepburn romaji, and optionally to
+/// Kunrei-shiki romaji, normalized with [`normalize_romaji`]. Queries are normalized the
+/// same way by [`RomajiTokenizer`](crate::romaji::RomajiTokenizer).
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::token_filter::japanese_romaji::JapaneseRomajiTokenFilter;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// // "東京" is emitted together with "tokyo"
+/// tokenizer.append_token_filter(JapaneseRomajiTokenFilter::new(false).into());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct JapaneseRomajiTokenFilter {
+    systems: Vec<RomajiSystem>,
+}
+
+impl JapaneseRomajiTokenFilter {
+    /// Creates a new `JapaneseRomajiTokenFilter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `kunrei` - Whether to also emit Kunrei-shiki romaji when it differs from Hepburn
+    pub fn new(kunrei: bool) -> Self {
+        let mut systems = vec![RomajiSystem::Hepburn];
+        if kunrei {
+            systems.push(RomajiSystem::Kunrei);
+        }
+        Self { systems }
+    }
+
+    /// Returns the distinct romaji of a token's reading that differ from its surface.
+    fn romanize(&self, token: &mut Token<'_>) -> Vec<String> {
+        let Some(reading) = token.get("reading") else {
+            return Vec::new();
+        };
+
+        let mut romaji: Vec<String> = Vec::with_capacity(self.systems.len());
+        for system in &self.systems {
+            let Some(transliterated) = to_romaji(reading, *system) else {
+                return Vec::new();
+            };
+            let normalized = normalize_romaji(&transliterated);
+            if !normalized.is_empty() && !romaji.contains(&normalized) {
+                romaji.push(normalized);
+            }
+        }
+        romaji.retain(|romaji| romaji.as_str() != token.surface.as_ref());
+
+        romaji
+    }
+}
+
+impl Default for JapaneseRomajiTokenFilter {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl TokenFilter for JapaneseRomajiTokenFilter {
+    fn name(&self) -> &'static str {
+        JAPANESE_ROMAJI_TOKEN_FILTER_NAME
+    }
+
+    /// Inserts the romaji tokens after each token with a katakana reading.
+    fn apply(&self, tokens: &mut Vec<Token<'_>>) -> LinderaResult<()> {
+        let mut new_tokens = Vec::with_capacity(tokens.len() * 2);
+        for mut token in tokens.drain(..) {
+            let romaji = self.romanize(&mut token);
+            let stacked: Vec<Token<'_>> = romaji
+                .into_iter()
+                .map(|romaji| {
+                    let mut stacked = token.clone();
+                    stacked.surface = Cow::Owned(romaji);
+                    stacked
+                })
+                .collect();
+
+            new_tokens.push(token);
+            new_tokens.extend(stacked);
+        }
+        *tokens = new_tokens;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "embed-ipadic")]
+mod tests {
+    use tantivy::collector::Count;
+    use tantivy::doc;
+    use tantivy::query::QueryParser;
+    use tantivy::schema::Schema;
+    use tantivy::tokenizer::TokenizerManager;
+    use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+    use crate::romaji::RomajiTokenizer;
+    use crate::test_helpers::{TOKENIZER_NAME, create_index_with, ipadic_tokenizer, text_options};
+    use crate::tokenizer::LinderaTokenizer;
+
+    use super::JapaneseRomajiTokenFilter;
+
+    fn tokenizer(kunrei: bool) -> LinderaTokenizer {
+        let mut tokenizer = ipadic_tokenizer();
+        tokenizer.append_token_filter(JapaneseRomajiTokenFilter::new(kunrei).into());
+        tokenizer
+    }
+
+    fn token_stream_helper(tokenizer: &mut LinderaTokenizer, text: &str) -> Vec<Token> {
+        let mut tokens: Vec<Token> = vec![];
+        tokenizer
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_japanese_romaji_stacks_hepburn() {
+        let tokens = token_stream_helper(&mut tokenizer(false), "東京空港");
+        let texts: Vec<(&str, usize)> = tokens
+            .iter()
+            .map(|token| (token.text.as_str(), token.position))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![("東京", 0), ("tokyo", 0), ("空港", 1), ("kuko", 1)]
+        );
+        assert_eq!((tokens[1].offset_from, tokens[1].offset_to), (0, 6));
+    }
+
+    #[test]
+    fn test_japanese_romaji_stacks_kunrei() {
+        let tokens = token_stream_helper(&mut tokenizer(true), "新宿");
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, vec!["新宿", "shinjuku", "sinzyuku"]);
+    }
+
+    #[test]
+    fn test_japanese_romaji_query() {
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", text_options());
+        let schema = schema_builder.build();
+        let index = create_index_with(
+            schema.clone(),
+            tokenizer(false),
+            [doc!(title => "東京空港")],
+        );
+
+        let query_tokenizers = TokenizerManager::default();
+        query_tokenizers.register(TOKENIZER_NAME, RomajiTokenizer::default());
+        let mut query_parser = QueryParser::new(schema, vec![title], query_tokenizers);
+        query_parser.set_conjunction_by_default();
+
+        let searcher = index.reader().unwrap().searcher();
+        for text in ["tokyo kuukou", "Tōkyō Kūkō", "\"toukyou kuukou\""] {
+            let query = query_parser.parse_query(text).unwrap();
+            assert_eq!(searcher.search(&query, &Count).unwrap(), 1, "query {text}");
+        }
+    }
+}
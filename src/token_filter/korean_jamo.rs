@@ -0,0 +1,310 @@
+//! Korean jamo token filter.
+//!
+//! This module provides the [`KoreanJamoTokenFilter`], which stacks the jamo decomposition
+//! and the initial consonants (choseong) of each Korean token on the token, so that
+//! partially composed input such as "ㅎㄴㄷ" or "ㅎㅏㄴ" can match "하네다".
+
+use std::borrow::Cow;
+
+use lindera::LinderaResult;
+use lindera::token::Token;
+use lindera::token_filter::TokenFilter;
+
+pub const KOREAN_JAMO_TOKEN_FILTER_NAME: &str = "korean_jamo";
+
+const SYLLABLE_START: u32 = 0xAC00;
+const SYLLABLE_END: u32 = 0xD7A3;
+const JUNGSEONG_COUNT: u32 = 21;
+const JONGSEONG_COUNT: u32 = 28;
+
+const CHOSEONG: [char; 19] = [
+    'ㄱ', 'ㄲ', 'ㄴ', 'ㄷ', 'ㄸ', 'ㄹ', 'ㅁ', 'ㅂ', 'ㅃ', 'ㅅ', 'ㅆ', 'ㅇ', 'ㅈ', 'ㅉ', 'ㅊ', 'ㅋ',
+    'ㅌ', 'ㅍ', 'ㅎ',
+];
+
+/// The medial vowels, with the compound vowels split into the keystrokes that type them.
+const JUNGSEONG: [&str; 21] = [
+    "ㅏ", "ㅐ", "ㅑ", "ㅒ", "ㅓ", "ㅔ", "ㅕ", "ㅖ", "ㅗ", "ㅗㅏ", "ㅗㅐ", "ㅗㅣ", "ㅛ", "ㅜ",
+    "ㅜㅓ", "ㅜㅔ", "ㅜㅣ", "ㅠ", "ㅡ", "ㅡㅣ", "ㅣ",
+];
+
+/// The final consonants, with the consonant clusters split into the keystrokes that type
+/// them. The first entry stands for a syllable without a final consonant.
+const JONGSEONG: [&str; 28] = [
+    "", "ㄱ", "ㄲ", "ㄱㅅ", "ㄴ", "ㄴㅈ", "ㄴㅎ", "ㄷ", "ㄹ", "ㄹㄱ", "ㄹㅁ", "ㄹㅂ", "ㄹㅅ",
+    "ㄹㅌ", "ㄹㅍ", "ㄹㅎ", "ㅁ", "ㅂ", "ㅂㅅ", "ㅅ", "ㅆ", "ㅇ", "ㅈ", "ㅊ", "ㅋ", "ㅌ", "ㅍ",
+    "ㅎ",
+];
+
+/// Returns the index of a precomposed Hangul syllable, or `None` for any other character.
+fn syllable_index(c: char) -> Option<u32> {
+    let code = c as u32;
+    (SYLLABLE_START..=SYLLABLE_END)
+        .contains(&code)
+        .then(|| code - SYLLABLE_START)
+}
+
+/// Splits a compound compatibility jamo (e.g. "ㅘ", "ㄺ") into the keystrokes that type it.
+fn split_compatibility_jamo(c: char) -> Option<&'static str> {
+    let split = match c {
+        'ㅘ' => "ㅗㅏ",
+        'ㅙ' => "ㅗㅐ",
+        'ㅚ' => "ㅗㅣ",
+        'ㅝ' => "ㅜㅓ",
+        'ㅞ' => "ㅜㅔ",
+        'ㅟ' => "ㅜㅣ",
+        'ㅢ' => "ㅡㅣ",
+        'ㄳ' => "ㄱㅅ",
+        'ㄵ' => "ㄴㅈ",
+        'ㄶ' => "ㄴㅎ",
+        'ㄺ' => "ㄹㄱ",
+        'ㄻ' => "ㄹㅁ",
+        'ㄼ' => "ㄹㅂ",
+        'ㄽ' => "ㄹㅅ",
+        'ㄾ' => "ㄹㅌ",
+        'ㄿ' => "ㄹㅍ",
+        'ㅀ' => "ㄹㅎ",
+        'ㅄ' => "ㅂㅅ",
+        _ => return None,
+    };
+    Some(split)
+}
+
+/// Decomposes the Hangul syllables of a text into compatibility jamo.
+///
+/// Compound vowels and consonant clusters are split into the jamo typed on a keyboard, so
+/// the decomposition of a partially typed word is a prefix of the decomposition of the
+/// complete word. Other characters are kept as is.
+///
+/// # Examples
+///
+/// ```
+/// use lindera_tantivy::token_filter::korean_jamo::decompose_jamo;
+///
+/// assert_eq!(decompose_jamo("하네다"), "ㅎㅏㄴㅔㄷㅏ");
+/// assert_eq!(decompose_jamo("닭"), "ㄷㅏㄹㄱ");
+/// ```
+pub fn decompose_jamo(text: &str) -> String {
+    let mut jamo = String::with_capacity(text.len() * 3);
+    for c in text.chars() {
+        if let Some(index) = syllable_index(c) {
+            let choseong = index / (JUNGSEONG_COUNT * JONGSEONG_COUNT);
+            let jungseong = index % (JUNGSEONG_COUNT * JONGSEONG_COUNT) / JONGSEONG_COUNT;
+            let jongseong = index % JONGSEONG_COUNT;
+            jamo.push(CHOSEONG[choseong as usize]);
+            jamo.push_str(JUNGSEONG[jungseong as usize]);
+            jamo.push_str(JONGSEONG[jongseong as usize]);
+        } else if let Some(split) = split_compatibility_jamo(c) {
+            jamo.push_str(split);
+        } else {
+            jamo.push(c);
+        }
+    }
+    jamo
+}
+
+/// Replaces the Hangul syllables of a text with their initial consonants (choseong).
+///
+/// Other characters are kept as is.
+///
+/// # Examples
+///
+/// ```
+/// use lindera_tantivy::token_filter::korean_jamo::choseong;
+///
+/// assert_eq!(choseong("하네다"), "ㅎㄴㄷ");
+/// ```
+pub fn choseong(text: &str) -> String {
+    text.chars()
+        .map(|c| match syllable_index(c) {
+            Some(index) => CHOSEONG[(index / (JUNGSEONG_COUNT * JONGSEONG_COUNT)) as usize],
+            None => c,
+        })
+        .collect()
+}
+
+/// A token filter that emits the jamo decomposition of Korean tokens as stacked tokens.
+///
+/// The [`decompose_jamo`] form and, optionally, the [`choseong`] form of tokens containing
+/// Hangul syllables are stacked, for prefix queries typed with an unfinished syllable or
+/// with initial consonants only. Queries should go through [`decompose_jamo`] too, as
+/// [`jamo_query`](crate::autocomplete::jamo_query) does for autocomplete fields.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::token_filter::korean_jamo::KoreanJamoTokenFilter;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let dictionary = load_dictionary("embedded://ko-dic")?;
+/// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+/// let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// // "하네다" is emitted together with "ㅎㅏㄴㅔㄷㅏ" and "ㅎㄴㄷ"
+/// tokenizer.append_token_filter(KoreanJamoTokenFilter::new(true).into());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct KoreanJamoTokenFilter {
+    choseong: bool,
+}
+
+impl KoreanJamoTokenFilter {
+    /// Creates a new `KoreanJamoTokenFilter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `choseong` - Whether to also emit the initial consonants of each token
+    pub fn new(choseong: bool) -> Self {
+        Self { choseong }
+    }
+
+    /// Returns the distinct jamo forms of a token that differ from its surface.
+    fn forms(&self, surface: &str) -> Vec<String> {
+        if !surface.chars().any(|c| syllable_index(c).is_some()) {
+            return Vec::new();
+        }
+
+        let mut forms = vec![decompose_jamo(surface)];
+        if self.choseong {
+            let choseong = choseong(surface);
+            if !forms.contains(&choseong) {
+                forms.push(choseong);
+            }
+        }
+        forms
+    }
+}
+
+impl Default for KoreanJamoTokenFilter {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl TokenFilter for KoreanJamoTokenFilter {
+    fn name(&self) -> &'static str {
+        KOREAN_JAMO_TOKEN_FILTER_NAME
+    }
+
+    /// Inserts the jamo tokens after each token containing Hangul syllables.
+    fn apply(&self, tokens: &mut Vec<Token<'_>>) -> LinderaResult<()> {
+        let mut new_tokens = Vec::with_capacity(tokens.len() * 2);
+        for token in tokens.drain(..) {
+            let stacked: Vec<Token<'_>> = self
+                .forms(&token.surface)
+                .into_iter()
+                .map(|form| {
+                    let mut stacked = token.clone();
+                    stacked.surface = Cow::Owned(form);
+                    stacked
+                })
+                .collect();
+
+            new_tokens.push(token);
+            new_tokens.extend(stacked);
+        }
+        *tokens = new_tokens;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{choseong, decompose_jamo};
+
+    #[test]
+    fn test_decompose_jamo() {
+        assert_eq!(decompose_jamo("공항"), "ㄱㅗㅇㅎㅏㅇ");
+        assert_eq!(decompose_jamo("과자"), "ㄱㅗㅏㅈㅏ");
+        assert_eq!(decompose_jamo("값"), "ㄱㅏㅂㅅ");
+        assert_eq!(decompose_jamo("ㅘㄳ"), "ㅗㅏㄱㅅ");
+        assert_eq!(decompose_jamo("KTX역"), "KTXㅇㅕㄱ");
+        assert!(decompose_jamo("하네다").starts_with(&decompose_jamo("하넫")));
+    }
+
+    #[test]
+    fn test_choseong() {
+        assert_eq!(choseong("김포공항"), "ㄱㅍㄱㅎ");
+        assert_eq!(choseong("ㅎㄴㄷ"), "ㅎㄴㄷ");
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ko-dic")]
+    fn test_korean_jamo_stacks_forms() {
+        use lindera::mode::Mode;
+        use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+        use crate::tokenizer::LinderaTokenizer;
+
+        use super::KoreanJamoTokenFilter;
+        let mut tokenizer =
+            LinderaTokenizer::from_dictionary("embedded://ko-dic", Mode::Normal, None).unwrap();
+        tokenizer.append_token_filter(KoreanJamoTokenFilter::new(true).into());
+
+        let mut tokens: Vec<Token> = vec![];
+        tokenizer
+            .token_stream("하네다 공항")
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+
+        let surfaces: Vec<&Token> = tokens
+            .iter()
+            .filter(|token| !token.text.starts_with(|c: char| ('ㄱ'..='ㅣ').contains(&c)))
+            .collect();
+        assert_eq!(tokens.len(), surfaces.len() * 3);
+        for (i, surface) in surfaces.iter().enumerate() {
+            let jamo = &tokens[i * 3 + 1];
+            let initials = &tokens[i * 3 + 2];
+            assert_eq!(jamo.text, decompose_jamo(&surface.text));
+            assert_eq!(initials.text, choseong(&surface.text));
+            assert_eq!(jamo.position, surface.position);
+            assert_eq!(initials.offset_from, surface.offset_from);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ko-dic")]
+    fn test_korean_jamo_search() {
+        use lindera::mode::Mode;
+        use tantivy::collector::Count;
+        use tantivy::doc;
+        use tantivy::schema::Schema;
+
+        use crate::autocomplete::{index_tokenizer, jamo_query, text_options};
+        use crate::test_helpers::{TOKENIZER_NAME, create_index_with};
+        use crate::tokenizer::LinderaTokenizer;
+
+        use super::KoreanJamoTokenFilter;
+
+        let mut schema_builder = Schema::builder();
+        let suggest = schema_builder.add_text_field("suggest", text_options(TOKENIZER_NAME));
+        let mut tokenizer =
+            LinderaTokenizer::from_dictionary("embedded://ko-dic", Mode::Normal, None).unwrap();
+        tokenizer.append_token_filter(KoreanJamoTokenFilter::new(true).into());
+        let index = create_index_with(
+            schema_builder.build(),
+            index_tokenizer(&tokenizer, 1, 20, false).unwrap(),
+            ["하네다 공항", "김포 공항"].map(|text| doc!(suggest => text)),
+        );
+
+        let searcher = index.reader().unwrap().searcher();
+        let count = |input: &str| {
+            searcher
+                .search(&jamo_query(suggest, input, 20), &Count)
+                .unwrap()
+        };
+        assert_eq!(count("ㅎㄴㄷ"), 1);
+        assert_eq!(count("ㅎㄴ"), 1);
+        assert_eq!(count("하넫"), 1);
+        assert_eq!(count("하네다"), 1);
+        assert_eq!(count("ㄱㅎ"), 2);
+        assert_eq!(count("ㄱㅍ ㄱㅎ"), 1);
+        assert_eq!(count("ㅂ"), 0);
+    }
+}
@@ -0,0 +1,212 @@
+//! Tantivy token filter bridge.
+//!
+//! This module provides the [`TantivyTokenFilter`], which applies a Tantivy token filter
+//! such as `Stemmer`, `AsciiFoldingFilter` or `RemoveLongFilter` inside the Lindera
+//! tokenization pipeline.
+
+use std::borrow::Cow;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use tantivy_tokenizer_api::{
+    Token, TokenFilter as TTokenFilter, TokenStream, Tokenizer as TTokenizer,
+};
+
+use lindera::LinderaResult;
+use lindera::token::Token as LToken;
+use lindera::token_filter::TokenFilter;
+
+pub const TANTIVY_TOKEN_FILTER_NAME: &str = "tantivy_filter";
+
+/// A Lindera token filter that applies a Tantivy token filter.
+///
+/// The tokens are replayed through the Tantivy token filter, and every token it emits is
+/// mapped back to the Lindera token it was produced from. The emitted text replaces the
+/// surface of the token, while the details of the token (part-of-speech tags, readings,
+/// etc.) are kept, so the filters appended after this one can still use them. Tokens
+/// removed by the Tantivy filter are removed, and tokens split by it share the details
+/// of the original token.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use lindera::dictionary::load_dictionary;
+/// use lindera::mode::Mode;
+/// use lindera::segmenter::Segmenter;
+/// use lindera_tantivy::token_filter::tantivy_filter::TantivyTokenFilter;
+/// use lindera_tantivy::tokenizer::LinderaTokenizer;
+/// use tantivy::tokenizer::{AsciiFoldingFilter, RemoveLongFilter};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mode = Mode::Normal;
+/// let dictionary = load_dictionary("embedded://ipadic")?;
+/// let segmenter = Segmenter::new(mode, dictionary, None);
+/// let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+///
+/// tokenizer
+///     .append_token_filter(TantivyTokenFilter::new(AsciiFoldingFilter).into())
+///     .append_token_filter(TantivyTokenFilter::new(RemoveLongFilter::limit(40)).into());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TantivyTokenFilter<F> {
+    token_filter: F,
+}
+
+impl<F: TTokenFilter + Clone> TantivyTokenFilter<F> {
+    /// Creates a new `TantivyTokenFilter`.
+    ///
+    /// # Arguments
+    ///
+    /// * `token_filter` - The Tantivy token filter to apply
+    pub fn new(token_filter: F) -> Self {
+        Self { token_filter }
+    }
+}
+
+impl<F: TTokenFilter + Clone> TokenFilter for TantivyTokenFilter<F> {
+    fn name(&self) -> &'static str {
+        TANTIVY_TOKEN_FILTER_NAME
+    }
+
+    /// Applies the Tantivy token filter to the tokens.
+    ///
+    /// The offsets, positions and surfaces of the tokens are taken from the tokens emitted
+    /// by the Tantivy token filter.
+    fn apply(&self, tokens: &mut Vec<LToken<'_>>) -> LinderaResult<()> {
+        let replay = ReplayTokenizer {
+            tokens: Arc::new(
+                tokens
+                    .iter()
+                    .map(|token| Token {
+                        offset_from: token.byte_start,
+                        offset_to: token.byte_end,
+                        position: token.position,
+                        text: token.surface.to_string(),
+                        position_length: token.position_length,
+                    })
+                    .collect(),
+            ),
+            current: Arc::new(AtomicUsize::new(0)),
+        };
+        let current = replay.current.clone();
+
+        let mut tokenizer = self.token_filter.clone().transform(replay);
+        let mut token_stream = tokenizer.token_stream("");
+
+        let mut new_tokens = Vec::with_capacity(tokens.len());
+        while token_stream.advance() {
+            let token = token_stream.token();
+            let mut new_token = tokens[current.load(Ordering::Relaxed)].clone();
+            new_token.surface = Cow::Owned(token.text.clone());
+            new_token.byte_start = token.offset_from;
+            new_token.byte_end = token.offset_to;
+            new_token.position = token.position;
+            new_token.position_length = token.position_length;
+            new_tokens.push(new_token);
+        }
+        *tokens = new_tokens;
+
+        Ok(())
+    }
+}
+
+/// A Tantivy tokenizer that replays already produced tokens, ignoring its input text.
+///
+/// The index of the token it emitted last is shared through `current`, which tells what
+/// token a token emitted by the filter on top of it was produced from.
+#[derive(Clone)]
+struct ReplayTokenizer {
+    tokens: Arc<Vec<Token>>,
+    current: Arc<AtomicUsize>,
+}
+
+impl TTokenizer for ReplayTokenizer {
+    type TokenStream<'a> = ReplayTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, _text: &'a str) -> ReplayTokenStream<'a> {
+        ReplayTokenStream {
+            tokens: &self.tokens,
+            current: &self.current,
+            next_index: 0,
+            token: Token::default(),
+        }
+    }
+}
+
+struct ReplayTokenStream<'a> {
+    tokens: &'a [Token],
+    current: &'a AtomicUsize,
+    next_index: usize,
+    token: Token,
+}
+
+impl TokenStream for ReplayTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        let Some(token) = self.tokens.get(self.next_index) else {
+            return false;
+        };
+
+        self.token.clone_from(token);
+        self.current.store(self.next_index, Ordering::Relaxed);
+        self.next_index += 1;
+        true
+    }
+
+    fn token(&self) -> &Token {
+        &self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.token
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "embed-ipadic")]
+mod tests {
+    use std::collections::HashSet;
+
+    use lindera::token_filter::japanese_stop_tags::JapaneseStopTagsTokenFilter;
+    use tantivy::tokenizer::{LowerCaser, RemoveLongFilter};
+    use tantivy_tokenizer_api::{Token, TokenStream, Tokenizer};
+
+    use crate::test_helpers::ipadic_tokenizer;
+    use crate::tokenizer::LinderaTokenizer;
+
+    use super::TantivyTokenFilter;
+
+    fn collect_tokens(tokenizer: &mut LinderaTokenizer, text: &str) -> Vec<Token> {
+        let mut tokens: Vec<Token> = vec![];
+        tokenizer
+            .token_stream(text)
+            .process(&mut |token: &Token| tokens.push(token.clone()));
+        tokens
+    }
+
+    #[test]
+    fn test_tantivy_token_filter_removes_tokens() {
+        let mut tokenizer = ipadic_tokenizer();
+        tokenizer.append_token_filter(TantivyTokenFilter::new(RemoveLongFilter::limit(10)).into());
+
+        let tokens = collect_tokens(&mut tokenizer, "羽田空港限定トートバッグ");
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0].text, "限定");
+        assert_eq!(tokens[0].position, 1);
+        assert_eq!((tokens[0].offset_from, tokens[0].offset_to), (12, 18));
+    }
+
+    #[test]
+    fn test_tantivy_token_filter_keeps_details() {
+        let mut tokenizer = ipadic_tokenizer();
+        let tags = HashSet::from(["助詞,連体化".to_string()]);
+        tokenizer
+            .append_token_filter(TantivyTokenFilter::new(LowerCaser).into())
+            .append_token_filter(JapaneseStopTagsTokenFilter::new(tags).into());
+
+        let tokens = collect_tokens(&mut tokenizer, "TOKYOの空港");
+        let texts: Vec<&str> = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(texts, vec!["tokyo", "空港"]);
+    }
+}
@@ -3,18 +3,49 @@
 //! This module provides the [`LinderaTokenizer`] struct, which implements Tantivy's
 //! [`Tokenizer`] trait using Lindera's morphological analysis capabilities.
 
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::env;
+use std::fs::File;
 use std::path::Path;
+use std::str::FromStr;
 
 use tantivy::Result;
 use tantivy::TantivyError;
+use tantivy::tokenizer::PreTokenizedString;
 use tantivy_tokenizer_api::{Token, Tokenizer};
 
-use lindera::character_filter::BoxCharacterFilter;
-use lindera::token_filter::BoxTokenFilter;
-use lindera::tokenizer::{Tokenizer as LTokenizer, TokenizerBuilder};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
+use lindera::LinderaResult;
+use lindera::character_filter::{BoxCharacterFilter, CharacterFilterLoader, OffsetMapping};
+use lindera::dictionary::{UserDictionary, load_user_dictionary};
+use lindera::mode::Mode;
+use lindera::segmenter::Segmenter;
+use lindera::token::Token as LToken;
+use lindera::token_filter::{BoxTokenFilter, TokenFilterLoader};
+use lindera::tokenizer::TokenizerConfig;
+
+use crate::dictionary::{load_dictionary, load_mmap_dictionary};
+use crate::segmenter::SharedSegmenter;
 use crate::stream::LinderaTokenStream;
 
+/// How the positions left by tokens removed by token filters are handled.
+///
+/// Token filters such as `japanese_stop_tags` remove tokens but keep the positions of the
+/// remaining tokens as Lindera assigned them, which leaves gaps in the positions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PositionGaps {
+    /// Keeps the gaps, so a phrase query only matches across a removed token with a slop,
+    /// as in Lucene.
+    #[default]
+    Preserve,
+    /// Renumbers the positions so that the remaining tokens are consecutive, so a phrase
+    /// query matches across a removed token without a slop.
+    Compact,
+}
+
 /// A Tantivy tokenizer that uses Lindera for morphological analysis.
 ///
 /// `LinderaTokenizer` wraps a Lindera tokenizer and provides an implementation of
@@ -27,6 +58,11 @@ use crate::stream::LinderaTokenStream;
 /// The tokenizer supports character filters and token filters to customize the
 /// tokenization process.
 ///
+/// The constructors load their dictionary through the [`dictionary`](crate::dictionary)
+/// registry, except [`from_segmenter`](Self::from_segmenter), which is given a loaded one.
+/// The tokenizers built from the same dictionary, and the clones of a tokenizer, share one
+/// copy of it, so cloning a tokenizer for each field or indexing thread is cheap.
+///
 /// # Examples
 ///
 /// ## Creating from a Segmenter
@@ -60,10 +96,42 @@ use crate::stream::LinderaTokenStream;
 /// ```
 #[derive(Clone)]
 pub struct LinderaTokenizer {
-    tokenizer: LTokenizer,
+    pipeline: Pipeline,
     token: Token,
 }
 
+/// The analysis pipeline of a `LinderaTokenizer`, kept apart from its Tantivy token so that
+/// the tokens can borrow the pipeline while the token is borrowed mutably.
+struct Pipeline {
+    segmenter: SharedSegmenter,
+    character_filters: Vec<BoxCharacterFilter>,
+    token_filters: Vec<BoxTokenFilter>,
+    nbest: usize,
+    nbest_cost_threshold: Option<i64>,
+    position_gaps: PositionGaps,
+}
+
+impl Clone for Pipeline {
+    fn clone(&self) -> Self {
+        Pipeline {
+            segmenter: self.segmenter.clone(),
+            character_filters: self
+                .character_filters
+                .iter()
+                .map(|character_filter| character_filter.box_clone())
+                .collect(),
+            token_filters: self
+                .token_filters
+                .iter()
+                .map(|token_filter| token_filter.box_clone())
+                .collect(),
+            nbest: self.nbest,
+            nbest_cost_threshold: self.nbest_cost_threshold,
+            position_gaps: self.position_gaps,
+        }
+    }
+}
+
 impl LinderaTokenizer {
     /// Creates a new `LinderaTokenizer` from the `LINDERA_CONFIG_PATH` environment variable.
     ///
@@ -89,15 +157,9 @@ impl LinderaTokenizer {
     /// # }
     /// ```
     pub fn new() -> Result<LinderaTokenizer> {
-        let builder =
-            TokenizerBuilder::new().map_err(|e| TantivyError::InvalidArgument(format!("{e:?}")))?;
-        let tokenizer = builder
-            .build()
-            .map_err(|e| TantivyError::InvalidArgument(format!("{e:?}")))?;
-        Ok(LinderaTokenizer {
-            tokenizer,
-            token: Default::default(),
-        })
+        let config_path = env::var("LINDERA_CONFIG_PATH")
+            .map_err(|e| TantivyError::InvalidArgument(format!("LINDERA_CONFIG_PATH: {e:?}")))?;
+        LinderaTokenizer::from_file(Path::new(&config_path))
     }
 
     /// Creates a new `LinderaTokenizer` from a YAML configuration file.
@@ -130,15 +192,67 @@ impl LinderaTokenizer {
     /// # }
     /// ```
     pub fn from_file(file_path: &Path) -> Result<LinderaTokenizer> {
-        let builder = TokenizerBuilder::from_file(file_path)
-            .map_err(|e| TantivyError::InvalidArgument(format!("{e:?}")))?;
-        let tokenizer = builder
-            .build()
+        let file = File::open(file_path).map_err(|e| {
+            TantivyError::InvalidArgument(format!("{}: {e:?}", file_path.display()))
+        })?;
+        let config: TokenizerConfig = serde_yaml_ng::from_reader(file).map_err(|e| {
+            TantivyError::InvalidArgument(format!("{}: {e:?}", file_path.display()))
+        })?;
+        LinderaTokenizer::from_config(&config)
+    }
+
+    /// Builds a tokenizer from a Lindera tokenizer configuration.
+    ///
+    /// The configuration is read as `lindera::tokenizer::Tokenizer::from_config` reads it,
+    /// and the filters are loaded by Lindera, but the dictionary is loaded through the
+    /// registry, which Lindera's builder cannot do.
+    fn from_config(config: &TokenizerConfig) -> Result<LinderaTokenizer> {
+        let segmenter_config = &config["segmenter"];
+        let uri = segmenter_config["dictionary"].as_str().ok_or_else(|| {
+            TantivyError::InvalidArgument("dictionary field is missing".to_string())
+        })?;
+        let dictionary = load_dictionary(uri)?;
+
+        let user_dictionary = segmenter_config["user_dictionary"]
+            .as_str()
+            .map(|uri| load_user_dictionary(uri, &dictionary.metadata))
+            .transpose()
             .map_err(|e| TantivyError::InvalidArgument(format!("{e:?}")))?;
-        Ok(LinderaTokenizer {
-            tokenizer,
-            token: Default::default(),
-        })
+        let mode = match &segmenter_config["mode"] {
+            serde_json::Value::Null => Ok(Mode::Normal),
+            serde_json::Value::String(mode) => {
+                Mode::from_str(mode).map_err(|e| TantivyError::InvalidArgument(format!("{e:?}")))
+            }
+            mode => serde_json::from_value(mode.clone())
+                .map_err(|e| TantivyError::InvalidArgument(format!("{e:?}"))),
+        }?;
+        let keep_whitespace = segmenter_config["keep_whitespace"]
+            .as_bool()
+            .unwrap_or(false);
+
+        let mut tokenizer = LinderaTokenizer::from_shared_segmenter(SharedSegmenter::new(
+            mode,
+            dictionary,
+            user_dictionary,
+            keep_whitespace,
+        ));
+        for setting in config["character_filters"].as_array().into_iter().flatten() {
+            if let Some(kind) = setting["kind"].as_str() {
+                let character_filter =
+                    CharacterFilterLoader::load_from_value(kind, &setting["args"])
+                        .map_err(|e| TantivyError::InvalidArgument(format!("{e:?}")))?;
+                tokenizer.append_character_filter(character_filter);
+            }
+        }
+        for setting in config["token_filters"].as_array().into_iter().flatten() {
+            if let Some(kind) = setting["kind"].as_str() {
+                let token_filter = TokenFilterLoader::load_from_value(kind, &setting["args"])
+                    .map_err(|e| TantivyError::InvalidArgument(format!("{e:?}")))?;
+                tokenizer.append_token_filter(token_filter);
+            }
+        }
+
+        Ok(tokenizer)
     }
 
     /// Creates a new `LinderaTokenizer` from a Lindera `Segmenter`.
@@ -148,6 +262,11 @@ impl LinderaTokenizer {
     /// to create a tokenizer as it allows you to specify the exact dictionary, mode,
     /// and user dictionary to use.
     ///
+    /// The dictionary of the segmenter is moved, not copied, and is shared by the clones of
+    /// the tokenizer. It is not added to the [`dictionary`](crate::dictionary) registry,
+    /// which keys the dictionaries by the URI they are loaded from; use
+    /// [`from_dictionary`](Self::from_dictionary) to share a dictionary between tokenizers.
+    ///
     /// # Arguments
     ///
     /// * `segmenter` - A configured Lindera `Segmenter` instance
@@ -172,9 +291,106 @@ impl LinderaTokenizer {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn from_segmenter(segmenter: lindera::segmenter::Segmenter) -> LinderaTokenizer {
+    pub fn from_segmenter(segmenter: Segmenter) -> LinderaTokenizer {
+        LinderaTokenizer::from_shared_segmenter(SharedSegmenter::from_segmenter(segmenter))
+    }
+
+    /// Creates a new `LinderaTokenizer` from the URI of a dictionary.
+    ///
+    /// The dictionary is loaded through the [`dictionary`](crate::dictionary) registry, so
+    /// the tokenizers built from the same URI load it once and share it.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI or path of the dictionary, e.g. "embedded://ipadic"
+    /// * `mode` - The segmentation mode
+    /// * `user_dictionary` - An optional user dictionary
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the dictionary cannot be loaded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use lindera::mode::Mode;
+    /// use lindera_tantivy::tokenizer::LinderaTokenizer;
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// // The dictionary is loaded once and shared by both tokenizers.
+    /// let title_tokenizer = LinderaTokenizer::from_dictionary("embedded://ipadic", Mode::Normal, None)?;
+    /// let body_tokenizer = LinderaTokenizer::from_dictionary("embedded://ipadic", Mode::Normal, None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_dictionary(
+        uri: &str,
+        mode: Mode,
+        user_dictionary: Option<UserDictionary>,
+    ) -> Result<LinderaTokenizer> {
+        let dictionary = load_dictionary(uri)?;
+        Ok(LinderaTokenizer::from_shared_segmenter(
+            SharedSegmenter::new(mode, dictionary, user_dictionary, false),
+        ))
+    }
+
+    /// Creates a new `LinderaTokenizer` from a compiled dictionary directory loaded with mmap.
+    ///
+    /// The prefix dictionary and the connection cost matrix, which make up most of the size
+    /// of a dictionary, are memory-mapped instead of being read into the heap, so the worker
+    /// processes of a host loading the same dictionary share its pages in the page cache, and
+    /// the binary does not need any of the `embed-*` features. The other, small parts of the
+    /// dictionary are read into the heap.
+    ///
+    /// The dictionary is loaded through the [`dictionary`](crate::dictionary) registry, so
+    /// the tokenizers built from the same directory in a process load it once and share it. The files of
+    /// the directory must not be modified while a tokenizer is in use.
+    ///
+    /// # Arguments
+    ///
+    /// * `dictionary_path` - Path to the compiled dictionary directory
+    /// * `mode` - The segmentation mode
+    /// * `user_dictionary` - An optional user dictionary
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory does not exist or does not contain a compiled
+    /// dictionary.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use std::path::Path;
+    /// use lindera::mode::Mode;
+    /// use lindera_tantivy::tokenizer::LinderaTokenizer;
+    ///
+    /// # fn main() -> tantivy::Result<()> {
+    /// let dictionary_path = Path::new("/usr/local/share/lindera/unidic");
+    /// let tokenizer = LinderaTokenizer::from_mmap_dictionary(dictionary_path, Mode::Normal, None)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_mmap_dictionary(
+        dictionary_path: &Path,
+        mode: Mode,
+        user_dictionary: Option<UserDictionary>,
+    ) -> Result<LinderaTokenizer> {
+        let dictionary = load_mmap_dictionary(dictionary_path)?;
+        Ok(LinderaTokenizer::from_shared_segmenter(
+            SharedSegmenter::new(mode, dictionary, user_dictionary, false),
+        ))
+    }
+
+    fn from_shared_segmenter(segmenter: SharedSegmenter) -> LinderaTokenizer {
         LinderaTokenizer {
-            tokenizer: LTokenizer::new(segmenter),
+            pipeline: Pipeline {
+                segmenter,
+                character_filters: Vec::new(),
+                token_filters: Vec::new(),
+                nbest: 1,
+                nbest_cost_threshold: None,
+                position_gaps: PositionGaps::default(),
+            },
             token: Default::default(),
         }
     }
@@ -218,7 +434,7 @@ impl LinderaTokenizer {
     /// # }
     /// ```
     pub fn append_character_filter(&mut self, character_filter: BoxCharacterFilter) -> &mut Self {
-        self.tokenizer.append_character_filter(character_filter);
+        self.pipeline.character_filters.push(character_filter);
 
         self
     }
@@ -264,10 +480,407 @@ impl LinderaTokenizer {
     /// # }
     /// ```
     pub fn append_token_filter(&mut self, token_filter: BoxTokenFilter) -> &mut Self {
-        self.tokenizer.token_filters.push(token_filter);
+        self.pipeline.token_filters.push(token_filter);
 
         self
     }
+
+    /// Emits tokens from the top-N segmentation paths as stacked tokens.
+    ///
+    /// Morphological ambiguity sometimes makes the best path split words wrongly. With
+    /// `n` greater than 1, the tokens of the other paths that are not on the best path are
+    /// emitted together with the best path tokens. The best path tokens keep their
+    /// positions, and the other tokens are stacked on the position of the best path token
+    /// they start in, with a `position_length` spanning up to the best path token they end
+    /// in. Tokens with the same offsets and surface are deduplicated.
+    ///
+    /// Tantivy ignores `position_length`, so phrase queries only match along the best path.
+    ///
+    /// This is meant to improve recall at index time. Setting `n` to 1 (the default)
+    /// disables it.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of segmentation paths to use
+    /// * `cost_threshold` - If set, paths whose cost exceeds the cost of the best path by
+    ///   more than this value are ignored
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use lindera::dictionary::load_dictionary;
+    /// use lindera::mode::Mode;
+    /// use lindera::segmenter::Segmenter;
+    /// use lindera_tantivy::tokenizer::LinderaTokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mode = Mode::Normal;
+    /// let dictionary = load_dictionary("embedded://ipadic")?;
+    /// let segmenter = Segmenter::new(mode, dictionary, None);
+    /// let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+    ///
+    /// // Stack the tokens of the 3 best segmentation paths
+    /// tokenizer.set_nbest(3, Some(5000));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_nbest(&mut self, n: usize, cost_threshold: Option<i64>) -> &mut Self {
+        self.pipeline.nbest = n.max(1);
+        self.pipeline.nbest_cost_threshold = cost_threshold;
+
+        self
+    }
+
+    /// Sets how the positions left by removed tokens are handled.
+    ///
+    /// By default, the gaps left by tokens removed by token filters are preserved, so
+    /// phrase queries need a slop to match across them. With [`PositionGaps::Compact`], the
+    /// positions are renumbered so that the remaining tokens are consecutive. Positions
+    /// spanned by a token with a `position_length` greater than 1 are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `position_gaps` - How to handle the gaps
+    ///
+    /// # Returns
+    ///
+    /// Returns a mutable reference to self for method chaining.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use std::collections::HashSet;
+    /// use lindera::token_filter::japanese_stop_tags::JapaneseStopTagsTokenFilter;
+    /// use lindera::dictionary::load_dictionary;
+    /// use lindera::mode::Mode;
+    /// use lindera::segmenter::Segmenter;
+    /// use lindera_tantivy::tokenizer::{LinderaTokenizer, PositionGaps};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let mode = Mode::Normal;
+    /// let dictionary = load_dictionary("embedded://ipadic")?;
+    /// let segmenter = Segmenter::new(mode, dictionary, None);
+    /// let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+    ///
+    /// // Remove the particle "の" and close the gaps it leaves
+    /// let tags = HashSet::from(["助詞,連体化".to_string()]);
+    /// tokenizer.append_token_filter(JapaneseStopTagsTokenFilter::new(tags).into());
+    /// tokenizer.set_position_gaps(PositionGaps::Compact);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_position_gaps(&mut self, position_gaps: PositionGaps) -> &mut Self {
+        self.pipeline.position_gaps = position_gaps;
+
+        self
+    }
+
+    /// Analyzes a text and returns the Lindera tokens.
+    ///
+    /// The text goes through the same pipeline as [`Tokenizer::token_stream`], including
+    /// the filters, N-best paths and position gap handling, but the tokens keep their
+    /// details (part-of-speech tags, readings, etc.), which the query helpers of this crate
+    /// rely on.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to analyze
+    ///
+    /// # Returns
+    ///
+    /// Returns the Lindera tokens of the text, or an error if the analysis fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use lindera::dictionary::load_dictionary;
+    /// use lindera::mode::Mode;
+    /// use lindera::segmenter::Segmenter;
+    /// use lindera_tantivy::tokenizer::LinderaTokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let dictionary = load_dictionary("embedded://ipadic")?;
+    /// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+    /// let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+    ///
+    /// for mut token in tokenizer.analyze("東京に行く")? {
+    ///     println!("{} {:?}", token.surface, token.get("major_pos"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn analyze<'a>(&'a self, text: &'a str) -> Result<Vec<LToken<'a>>> {
+        self.pipeline
+            .tokenize(text)
+            .map_err(|e| TantivyError::InvalidArgument(format!("{e:?}")))
+    }
+
+    /// Analyzes a text into a [`PreTokenizedString`].
+    ///
+    /// The tokens are the ones [`token_stream`](Tokenizer::token_stream) emits, and the
+    /// pre-tokenized string can be serialized with serde, e.g. to be sent to another process
+    /// and added to a document there with `TantivyDocument::add_pre_tokenized_text`. Tantivy
+    /// indexes pre-tokenized text as is, so the indexing process does not need a dictionary;
+    /// it still needs a tokenizer registered under the name in the indexing options of the
+    /// field, which is not run and can be a placeholder such as Tantivy's `RawTokenizer`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to analyze
+    ///
+    /// # Returns
+    ///
+    /// Returns the text and its tokens, or an error if the analysis fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use lindera::dictionary::load_dictionary;
+    /// use lindera::mode::Mode;
+    /// use lindera::segmenter::Segmenter;
+    /// use lindera_tantivy::tokenizer::LinderaTokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let dictionary = load_dictionary("embedded://ipadic")?;
+    /// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+    /// let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+    ///
+    /// let pre_tokenized_text = tokenizer.pre_tokenize("東京国際空港")?;
+    /// let json = serde_json::to_string(&pre_tokenized_text)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pre_tokenize(&self, text: &str) -> Result<PreTokenizedString> {
+        let tokens = self
+            .analyze(text)?
+            .into_iter()
+            .map(|token| Token {
+                offset_from: token.byte_start,
+                offset_to: token.byte_end,
+                position: token.position,
+                text: token.surface.into_owned(),
+                position_length: token.position_length,
+            })
+            .collect();
+
+        Ok(PreTokenizedString {
+            text: text.to_string(),
+            tokens,
+        })
+    }
+
+    /// Tokenizes a batch of texts.
+    ///
+    /// The tokens of each text are the ones [`token_stream`](Tokenizer::token_stream) emits.
+    /// With the `parallel` feature, the texts are tokenized in parallel on the global rayon
+    /// thread pool, or on the pool the method is called from with `ThreadPool::install`, and
+    /// the threads share the dictionary of the tokenizer instead of each working on a clone.
+    ///
+    /// # Arguments
+    ///
+    /// * `texts` - The texts to tokenize
+    ///
+    /// # Returns
+    ///
+    /// Returns the tokens of each text in the same order as the texts, or an error if the
+    /// analysis of a text fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use lindera::dictionary::load_dictionary;
+    /// use lindera::mode::Mode;
+    /// use lindera::segmenter::Segmenter;
+    /// use lindera_tantivy::tokenizer::LinderaTokenizer;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let dictionary = load_dictionary("embedded://ipadic")?;
+    /// let segmenter = Segmenter::new(Mode::Normal, dictionary, None);
+    /// let tokenizer = LinderaTokenizer::from_segmenter(segmenter);
+    ///
+    /// let batch = tokenizer.tokenize_batch(&["東京国際空港", "羽田空港"])?;
+    /// assert_eq!(batch.len(), 2);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tokenize_batch(&self, texts: &[&str]) -> Result<Vec<Vec<Token>>> {
+        #[cfg(feature = "parallel")]
+        let texts = texts.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let texts = texts.iter();
+
+        texts
+            .map(|text| Ok(self.pre_tokenize(text)?.tokens))
+            .collect()
+    }
+}
+
+impl Pipeline {
+    /// Tokenizes a text: applies the character filters, segments the text along the best
+    /// path or the N-best paths, applies the token filters to each path, maps the offsets
+    /// back to the original text, and handles the position gaps.
+    fn tokenize<'a>(&'a self, text: &'a str) -> LinderaResult<Vec<LToken<'a>>> {
+        let mut normalized_text = Cow::Borrowed(text);
+        let mut offset_mappings = Vec::new();
+        if !self.character_filters.is_empty() {
+            let text = normalized_text.to_mut();
+            for character_filter in &self.character_filters {
+                let offset_mapping = character_filter.apply(text)?;
+                if !offset_mapping.is_empty() {
+                    offset_mappings.push(offset_mapping);
+                }
+            }
+        }
+        let text_len = normalized_text.len();
+
+        let mut tokens = if self.nbest > 1 {
+            let mut paths = self.segmenter.segment_nbest(
+                normalized_text,
+                self.nbest,
+                self.nbest_cost_threshold,
+            );
+            for (tokens, _) in paths.iter_mut() {
+                self.filter(tokens, &offset_mappings, text_len)?;
+            }
+            merge_paths(paths)
+        } else {
+            let mut tokens = self.segmenter.segment(normalized_text);
+            self.filter(&mut tokens, &offset_mappings, text_len)?;
+            tokens
+        };
+        if self.position_gaps == PositionGaps::Compact {
+            compact_positions(&mut tokens);
+        }
+        Ok(tokens)
+    }
+
+    /// Applies the token filters to the tokens of a path, and maps their offsets back to the
+    /// text before the character filters, undoing the last filter first.
+    fn filter(
+        &self,
+        tokens: &mut Vec<LToken<'_>>,
+        offset_mappings: &[OffsetMapping],
+        text_len: usize,
+    ) -> LinderaResult<()> {
+        for token_filter in &self.token_filters {
+            token_filter.apply(tokens)?;
+        }
+        for token in tokens.iter_mut() {
+            for offset_mapping in offset_mappings.iter().rev() {
+                token.byte_start = offset_mapping.correct_offset(token.byte_start, text_len);
+                token.byte_end = offset_mapping.correct_offset(token.byte_end, text_len);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Merges the tokens of N-best segmentation paths into the best path.
+///
+/// The tokens of the other paths are deduplicated by their offsets and surfaces. The best
+/// path tokens keep their positions, and each alternative token is stacked on the position
+/// of the best path token it starts in, with a `position_length` spanning up to the best
+/// path token it ends in.
+fn merge_paths<'a>(paths: Vec<(Vec<LToken<'a>>, i64)>) -> Vec<LToken<'a>> {
+    let mut paths = paths.into_iter();
+    let Some((mut tokens, _)) = paths.next() else {
+        return Vec::new();
+    };
+
+    let mut seen: HashSet<(usize, usize, String)> = tokens
+        .iter()
+        .map(|token| (token.byte_start, token.byte_end, token.surface.to_string()))
+        .collect();
+    let mut alternatives = Vec::new();
+    for (path, _) in paths {
+        for token in path {
+            let key = (token.byte_start, token.byte_end, token.surface.to_string());
+            if seen.insert(key) {
+                alternatives.push(token);
+            }
+        }
+    }
+    if alternatives.is_empty() {
+        return tokens;
+    }
+
+    // The position of the best path token a start offset falls in, or of the next one if
+    // the offset is in a gap between them.
+    let start_position = |offset: usize| -> usize {
+        tokens
+            .iter()
+            .find(|token| token.byte_start <= offset && offset < token.byte_end)
+            .or_else(|| tokens.iter().find(|token| token.byte_start >= offset))
+            .or(tokens.last())
+            .map_or(0, |token| token.position)
+    };
+    // The position after the best path token an end offset falls in, or after the previous
+    // one if the offset is in a gap between them.
+    let end_position = |offset: usize| -> usize {
+        tokens
+            .iter()
+            .find(|token| token.byte_start < offset && offset <= token.byte_end)
+            .or_else(|| tokens.iter().rev().find(|token| token.byte_end <= offset))
+            .map_or(0, |token| token.position + token.position_length)
+    };
+
+    let alternatives: Vec<LToken<'a>> = alternatives
+        .into_iter()
+        .map(|mut token| {
+            let start = start_position(token.byte_start);
+            let end = end_position(token.byte_end);
+            token.position = start;
+            token.position_length = end.saturating_sub(start).max(1);
+            token
+        })
+        .collect();
+    tokens.extend(alternatives);
+
+    // Stable sort keeps the best path token first among the tokens on the same position.
+    tokens.sort_by_key(|token| token.position);
+
+    tokens
+}
+
+/// Renumbers the positions of the tokens so that no position is left uncovered.
+///
+/// A position is covered if a token starts on it or spans over it with its
+/// `position_length`. Uncovered positions are removed, and the positions and position
+/// lengths of the tokens are shifted accordingly.
+fn compact_positions(tokens: &mut [LToken<'_>]) {
+    let Some(len) = tokens
+        .iter()
+        .map(|token| token.position + token.position_length)
+        .max()
+    else {
+        return;
+    };
+
+    let mut covered = vec![false; len];
+    for token in tokens.iter() {
+        covered[token.position..token.position + token.position_length].fill(true);
+    }
+
+    // The number of covered positions before each position.
+    let mut compacted = Vec::with_capacity(len + 1);
+    let mut count = 0;
+    for is_covered in covered.iter() {
+        compacted.push(count);
+        if *is_covered {
+            count += 1;
+        }
+    }
+    compacted.push(count);
+
+    for token in tokens.iter_mut() {
+        let end = token.position + token.position_length;
+        token.position_length = compacted[end] - compacted[token.position];
+        token.position = compacted[token.position];
+    }
 }
 
 impl Tokenizer for LinderaTokenizer {
@@ -276,8 +889,9 @@ impl Tokenizer for LinderaTokenizer {
     #[inline]
     fn token_stream<'a>(&'a mut self, text: &'a str) -> LinderaTokenStream<'a> {
         self.token.reset();
+        let tokens = self.pipeline.tokenize(text).unwrap();
         LinderaTokenStream {
-            tokens: self.tokenizer.tokenize(text).unwrap(),
+            tokens,
             token: &mut self.token,
             current_index: 0,
         }
@@ -298,7 +912,12 @@ mod tests {
     use lindera::dictionary::load_dictionary;
     use lindera::mode::Mode;
 
+    #[cfg(feature = "embed-ipadic")]
+    use crate::test_helpers::{create_index_with, ipadic_tokenizer, text_options};
+
     use super::LinderaTokenizer;
+    #[cfg(feature = "embed-ipadic")]
+    use super::PositionGaps;
 
     fn token_stream_helper(text: &str, dictionary_uri: &str) -> Vec<Token> {
         let mode = Mode::Normal;
@@ -307,6 +926,10 @@ mod tests {
         let segmenter = Segmenter::new(mode, dictionary, user_dictionary);
         let mut tokenizer = LinderaTokenizer::from_segmenter(segmenter);
 
+        collect_tokens(&mut tokenizer, text)
+    }
+
+    fn collect_tokens(tokenizer: &mut LinderaTokenizer, text: &str) -> Vec<Token> {
         let mut token_stream = tokenizer.token_stream(text);
         let mut tokens: Vec<Token> = vec![];
         let mut add_token = |token: &Token| {
@@ -397,4 +1020,270 @@ mod tests {
         assert_token(&tokens[4], 4, "版", 18, 21);
         assert_token(&tokens[5], 5, "手提包", 21, 30);
     }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_tokenize_ipadic_nbest() {
+        let text = "すもももももももものうち";
+        let mut tokenizer = ipadic_tokenizer();
+        let best = collect_tokens(&mut tokenizer, text);
+
+        tokenizer.set_nbest(5, None);
+        let tokens = collect_tokens(&mut tokenizer, text);
+
+        // The best path is kept, and the alternatives are stacked on it.
+        assert!(tokens.len() > best.len());
+        for token in best.iter() {
+            assert!(
+                tokens.iter().any(|t| t.text == token.text
+                    && t.offset_from == token.offset_from
+                    && t.offset_to == token.offset_to),
+                "missing best path token {token:?}"
+            );
+        }
+        for pair in tokens.windows(2) {
+            assert!(pair[0].position <= pair[1].position, "unordered {pair:?}");
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_merge_paths_stacks_alternatives() {
+        use std::borrow::Cow;
+
+        use lindera::dictionary::WordId;
+        use lindera::token::Token as LToken;
+
+        use super::merge_paths;
+
+        // "東京都庁" segmented as "東京|都庁", "東|京都|庁" and "東京都|庁".
+        let dictionary = load_dictionary("embedded://ipadic").unwrap();
+        let path = |words: &[(&'static str, usize, usize)]| {
+            words
+                .iter()
+                .enumerate()
+                .map(|(position, (surface, start, end))| {
+                    LToken::new(
+                        Cow::Borrowed(*surface),
+                        *start,
+                        *end,
+                        position,
+                        WordId::default(),
+                        &dictionary,
+                        None,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let paths = vec![
+            (path(&[("東京", 0, 6), ("都庁", 6, 12)]), 0),
+            (path(&[("東", 0, 3), ("京都", 3, 9), ("庁", 9, 12)]), 10),
+            (path(&[("東京都", 0, 9), ("庁", 9, 12)]), 20),
+        ];
+
+        // The best path keeps its positions, and the other tokens are stacked on it.
+        let tokens: Vec<(String, usize, usize, usize, usize)> = merge_paths(paths)
+            .into_iter()
+            .map(|token| {
+                (
+                    token.surface.to_string(),
+                    token.byte_start,
+                    token.byte_end,
+                    token.position,
+                    token.position_length,
+                )
+            })
+            .collect();
+        let expected = [
+            ("東京", 0, 6, 0, 1),
+            ("東", 0, 3, 0, 1),
+            ("京都", 3, 9, 0, 2),
+            ("東京都", 0, 9, 0, 2),
+            ("都庁", 6, 12, 1, 1),
+            ("庁", 9, 12, 1, 1),
+        ]
+        .map(|(text, start, end, position, position_length)| {
+            (text.to_string(), start, end, position, position_length)
+        });
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_tokenize_ipadic_nbest_stacked_readings() {
+        use tantivy::collector::Count;
+        use tantivy::query::PhraseQuery;
+        use tantivy::schema::Schema;
+        use tantivy::{Term, doc};
+
+        use lindera::token_filter::japanese_kana::KanaKind;
+
+        use crate::token_filter::japanese_kana_fold::JapaneseKanaFoldTokenFilter;
+
+        let text = "関西国際空港に行く";
+        let mut tokenizer = ipadic_tokenizer();
+        tokenizer
+            .append_token_filter(JapaneseKanaFoldTokenFilter::new(KanaKind::Katakana, true).into());
+        let best = collect_tokens(&mut tokenizer, text);
+        tokenizer.set_nbest(5, None);
+        let tokens = collect_tokens(&mut tokenizer, text);
+
+        // The best path tokens and their stacked readings keep their positions.
+        assert!(tokens.len() > best.len());
+        for token in best.iter() {
+            assert!(
+                tokens.iter().any(|t| t.text == token.text
+                    && t.offset_from == token.offset_from
+                    && t.offset_to == token.offset_to
+                    && t.position == token.position),
+                "moved best path token {token:?}"
+            );
+        }
+
+        // A phrase along the best path still matches without slop.
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_text_field("text", text_options());
+        let index = create_index_with(schema_builder.build(), tokenizer, [doc!(field => text)]);
+
+        let mut terms = Vec::new();
+        for token in best.iter() {
+            if terms.len() == token.position {
+                terms.push(Term::from_field_text(field, &token.text));
+            }
+        }
+        let searcher = index.reader().unwrap().searcher();
+        let query = PhraseQuery::new(terms);
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 1);
+    }
+
+    #[cfg(feature = "embed-ipadic")]
+    fn phrase_query_count(position_gaps: PositionGaps, slop: u32) -> usize {
+        use std::collections::HashSet;
+
+        use lindera::token_filter::japanese_stop_tags::JapaneseStopTagsTokenFilter;
+        use tantivy::collector::Count;
+        use tantivy::query::PhraseQuery;
+        use tantivy::schema::Schema;
+        use tantivy::{Term, doc};
+
+        let mut schema_builder = Schema::builder();
+        let text = schema_builder.add_text_field("text", text_options());
+        let mut tokenizer = ipadic_tokenizer();
+        let tags = HashSet::from(["助詞,連体化".to_string()]);
+        tokenizer.append_token_filter(JapaneseStopTagsTokenFilter::new(tags).into());
+        tokenizer.set_position_gaps(position_gaps);
+        let index = create_index_with(
+            schema_builder.build(),
+            tokenizer,
+            [doc!(text => "東京の空港")],
+        );
+
+        let searcher = index.reader().unwrap().searcher();
+        let mut query = PhraseQuery::new(vec![
+            Term::from_field_text(text, "東京"),
+            Term::from_field_text(text, "空港"),
+        ]);
+        query.set_slop(slop);
+        searcher.search(&query, &Count).unwrap()
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_position_gaps_preserve() {
+        // "の" is removed, but its position is kept as a gap.
+        assert_eq!(phrase_query_count(PositionGaps::Preserve, 0), 0);
+        assert_eq!(phrase_query_count(PositionGaps::Preserve, 1), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_position_gaps_compact() {
+        assert_eq!(phrase_query_count(PositionGaps::Compact, 0), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_tokenize_batch() {
+        let mut tokenizer = ipadic_tokenizer();
+
+        let texts = [
+            "東京国際空港",
+            "",
+            "羽田空港限定トートバッグ",
+            "すもももももももものうち",
+        ];
+        let batch = tokenizer.tokenize_batch(&texts).unwrap();
+        assert_eq!(batch.len(), texts.len());
+        for (text, tokens) in texts.iter().zip(batch) {
+            assert_eq!(tokens, collect_tokens(&mut tokenizer, text));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_pre_tokenize() {
+        use tantivy::collector::Count;
+        use tantivy::query::TermQuery;
+        use tantivy::schema::{IndexRecordOption, Schema};
+        use tantivy::tokenizer::RawTokenizer;
+        use tantivy::{TantivyDocument, Term};
+
+        let mut tokenizer = ipadic_tokenizer();
+
+        let text = "羽田空港限定トートバッグ";
+        let pre_tokenized_text = tokenizer.pre_tokenize(text).unwrap();
+        assert_eq!(pre_tokenized_text.text, text);
+        assert_eq!(
+            pre_tokenized_text.tokens,
+            collect_tokens(&mut tokenizer, text)
+        );
+
+        // The indexing process only has a placeholder tokenizer.
+        let mut schema_builder = Schema::builder();
+        let title = schema_builder.add_text_field("title", text_options());
+        let mut document = TantivyDocument::default();
+        document.add_pre_tokenized_text(title, pre_tokenized_text);
+        let index = create_index_with(schema_builder.build(), RawTokenizer::default(), [document]);
+
+        let searcher = index.reader().unwrap().searcher();
+        let query = TermQuery::new(
+            Term::from_field_text(title, "空港"),
+            IndexRecordOption::Basic,
+        );
+        assert_eq!(searcher.search(&query, &Count).unwrap(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "embed-ipadic")]
+    fn test_from_config() {
+        use lindera::tokenizer::Tokenizer as LTokenizer;
+
+        let config = serde_json::json!({
+            "segmenter": { "dictionary": "embedded://ipadic", "mode": "normal" },
+            "character_filters": [{ "kind": "unicode_normalize", "args": { "kind": "nfkc" } }],
+            "token_filters": [{ "kind": "lowercase", "args": {} }],
+        });
+        let text = "ＴＯＫＹＯの羽田空港、限定トートバッグ";
+        let key = |token: &lindera::token::Token<'_>| {
+            (
+                token.surface.to_string(),
+                token.byte_start,
+                token.byte_end,
+                token.position,
+            )
+        };
+
+        // The tokens are the ones Lindera's tokenizer emits from the same configuration.
+        let lindera_tokenizer = LTokenizer::from_config(&config).unwrap();
+        let expected: Vec<_> = lindera_tokenizer
+            .tokenize(text)
+            .unwrap()
+            .iter()
+            .map(key)
+            .collect();
+        let tokenizer = LinderaTokenizer::from_config(&config).unwrap();
+        let tokens: Vec<_> = tokenizer.analyze(text).unwrap().iter().map(key).collect();
+        assert_eq!(tokens, expected);
+        assert_eq!(tokens[0].0, "tokyo");
+    }
 }